@@ -41,7 +41,7 @@ fn main() {
     let total_original_bytes = original_values.len() * std::mem::size_of::<u128>();
     println!("Original bytes: {total_original_bytes}");
     println!("Encoded bytes: {total_encoded_bytes}");
-    for (original, encoded) in original_values.into_iter().zip(encoded.into_iter()) {
+    for (original, encoded) in original_values.into_iter().zip(encoded) {
         println!("{original} encodes as {encoded:02x?}");
     }
 }
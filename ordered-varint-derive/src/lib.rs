@@ -0,0 +1,312 @@
+//! Provides `#[derive(Variable)]` for
+//! [ordered-varint](https://crates.io/crates/ordered-varint).
+//!
+//! This crate is re-exported by `ordered-varint` when its `derive` feature is
+//! enabled, and should not be used directly.
+
+#![forbid(unsafe_code)]
+#![warn(
+    clippy::cargo,
+    missing_docs,
+    clippy::nursery,
+    clippy::pedantic,
+    future_incompatible,
+    rust_2018_idioms
+)]
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::HashMap;
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Data, DataEnum, DeriveInput, Field, Fields, GenericParam,
+    LitInt, Variant,
+};
+
+/// Derives `ordered_varint::Variable` for a struct or enum.
+///
+/// Struct fields are encoded in declaration order. Enums are encoded as their
+/// variant's ordinal, encoded as an unsigned integer, followed by the
+/// variant's fields. Ordinals start at 0 and increase by one for each
+/// variant, unless they are overridden with `#[variable(ordinal = N)]`. When
+/// ordinals increase in declaration order, the encoded bytes sort in the same
+/// order as `#[derive(PartialOrd, Ord)]`.
+///
+/// Fields marked with `#[variable(descending)]` are encoded with their bytes
+/// inverted, causing them to sort in descending order.
+#[proc_macro_derive(Variable, attributes(variable))]
+pub fn derive_variable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(mut input: DeriveInput) -> syn::Result<TokenStream> {
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(::ordered_varint::Variable));
+        }
+    }
+
+    let Expansion {
+        max_len,
+        encoded_len,
+        encode,
+        decode,
+    } = match &input.data {
+        Data::Struct(data) => {
+            let bindings = field_bindings(&data.fields);
+            let pattern = fields_pattern(quote!(Self), &data.fields, &bindings);
+            let encoded_len = fields_len(&bindings);
+            let encode = encode_fields(&data.fields, &bindings)?;
+            let decode = decode_fields(quote!(Self), &data.fields)?;
+            Expansion {
+                max_len: fields_max_len(&data.fields),
+                encoded_len: quote! {
+                    let #pattern = self;
+                    #encoded_len
+                },
+                encode: quote! {
+                    let #pattern = self;
+                    #encode
+                },
+                decode: quote!(::core::result::Result::Ok(#decode)),
+            }
+        }
+        Data::Enum(data) => expand_enum(data)?,
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                "Variable cannot be derived for unions",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::ordered_varint::Variable for #name #ty_generics #where_clause {
+            const MAX_ENCODED_LEN: usize = #max_len;
+
+            #[allow(unused_variables)]
+            fn encoded_len(&self) -> usize {
+                #encoded_len
+            }
+
+            #[allow(unused_mut, unused_variables)]
+            fn encode_variable<__W: ::ordered_varint::io::Write>(
+                &self,
+                mut destination: __W,
+            ) -> ::core::result::Result<usize, ::ordered_varint::Error> {
+                let mut __total = 0_usize;
+                #encode
+                ::core::result::Result::Ok(__total)
+            }
+
+            #[allow(unused_mut, unused_variables)]
+            fn decode_variable<__R: ::ordered_varint::io::Read>(
+                source: __R,
+            ) -> ::core::result::Result<Self, ::ordered_varint::Error> {
+                let mut source = ::ordered_varint::__private::ElementReader::new(source);
+                #decode
+            }
+        }
+    })
+}
+
+/// The bodies of the generated `Variable` implementation.
+struct Expansion {
+    max_len: TokenStream,
+    encoded_len: TokenStream,
+    encode: TokenStream,
+    decode: TokenStream,
+}
+
+fn expand_enum(data: &DataEnum) -> syn::Result<Expansion> {
+    let mut max_len = quote!(0_usize);
+    let mut len_arms = Vec::new();
+    let mut encode_arms = Vec::new();
+    let mut decode_arms = Vec::new();
+    let mut seen = HashMap::new();
+    let mut next_ordinal = 0_u64;
+    for variant in &data.variants {
+        let ordinal = variant_ordinal(variant)?.unwrap_or(next_ordinal);
+        if let Some(previous) = seen.insert(ordinal, &variant.ident) {
+            return Err(syn::Error::new(
+                variant.span(),
+                format!("ordinal {ordinal} is already used by `{previous}`"),
+            ));
+        }
+        next_ordinal = ordinal.checked_add(1).ok_or_else(|| {
+            syn::Error::new(variant.span(), "the next variant's ordinal overflows u64")
+        })?;
+
+        let ident = &variant.ident;
+        let bindings = field_bindings(&variant.fields);
+        let pattern = fields_pattern(quote!(Self::#ident), &variant.fields, &bindings);
+        let variant_max_len = fields_max_len(&variant.fields);
+        max_len = quote! {{
+            let previous = #max_len;
+            let variant = #variant_max_len;
+            if previous > variant { previous } else { variant }
+        }};
+        let encoded_len = fields_len(&bindings);
+        len_arms.push(quote! {
+            #pattern => ::ordered_varint::Variable::encoded_len(&#ordinal) + #encoded_len,
+        });
+        let encode = encode_fields(&variant.fields, &bindings)?;
+        encode_arms.push(quote! {
+            #pattern => {
+                __total += ::ordered_varint::Variable::encode_variable(
+                    &#ordinal,
+                    &mut destination,
+                )?;
+                #encode
+            }
+        });
+        let decode = decode_fields(quote!(Self::#ident), &variant.fields)?;
+        decode_arms.push(quote!(#ordinal => ::core::result::Result::Ok(#decode),));
+    }
+
+    // An empty enum can't be referenced, so there is nothing to encode.
+    let (encoded_len, encode) = if data.variants.is_empty() {
+        (quote!(match *self {}), quote!(match *self {}))
+    } else {
+        (
+            quote! {
+                match self {
+                    #(#len_arms)*
+                }
+            },
+            quote! {
+                match self {
+                    #(#encode_arms)*
+                }
+            },
+        )
+    };
+    Ok(Expansion {
+        max_len: quote! {
+            <u64 as ::ordered_varint::Variable>::MAX_ENCODED_LEN.saturating_add(#max_len)
+        },
+        encoded_len,
+        encode,
+        decode: quote! {
+            let ordinal: u64 = ::ordered_varint::__private::ElementReader::decode(&mut source)?;
+            match ordinal {
+                #(#decode_arms)*
+                ordinal => ::core::result::Result::Err(
+                    ::ordered_varint::Error::UnknownVariant { ordinal },
+                ),
+            }
+        },
+    })
+}
+
+fn variant_ordinal(variant: &Variant) -> syn::Result<Option<u64>> {
+    let mut ordinal = None;
+    for attr in &variant.attrs {
+        if !attr.path().is_ident("variable") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("ordinal") {
+                ordinal = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `ordinal = N`"))
+            }
+        })?;
+    }
+    Ok(ordinal)
+}
+
+fn is_descending(field: &Field) -> syn::Result<bool> {
+    let mut descending = false;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("variable") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("descending") {
+                descending = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `descending`"))
+            }
+        })?;
+    }
+    Ok(descending)
+}
+
+fn field_bindings(fields: &Fields) -> Vec<Ident> {
+    (0..fields.len())
+        .map(|index| format_ident!("__field{index}"))
+        .collect()
+}
+
+/// Returns a pattern that binds each field of `path` to the corresponding
+/// identifier in `bindings`.
+fn fields_pattern(path: TokenStream, fields: &Fields, bindings: &[Ident]) -> TokenStream {
+    match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#names: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#bindings),*)),
+        Fields::Unit => path,
+    }
+}
+
+/// Returns the sum of each field's `MAX_ENCODED_LEN`. Descending fields
+/// encode to the same number of bytes.
+fn fields_max_len(fields: &Fields) -> TokenStream {
+    let types = fields.iter().map(|field| &field.ty);
+    quote! {
+        0_usize #(.saturating_add(<#types as ::ordered_varint::Variable>::MAX_ENCODED_LEN))*
+    }
+}
+
+fn fields_len(bindings: &[Ident]) -> TokenStream {
+    quote!(0_usize #(+ ::ordered_varint::Variable::encoded_len(#bindings))*)
+}
+
+fn encode_fields(fields: &Fields, bindings: &[Ident]) -> syn::Result<TokenStream> {
+    fields
+        .iter()
+        .zip(bindings)
+        .map(|(field, binding)| {
+            let function = if is_descending(field)? {
+                quote!(::ordered_varint::__private::encode_descending)
+            } else {
+                quote!(::ordered_varint::Variable::encode_variable)
+            };
+            Ok(quote!(__total += #function(#binding, &mut destination)?;))
+        })
+        .collect()
+}
+
+fn decode_fields(path: TokenStream, fields: &Fields) -> syn::Result<TokenStream> {
+    let values = fields
+        .iter()
+        .map(|field| {
+            let function = if is_descending(field)? {
+                quote!(::ordered_varint::__private::ElementReader::decode_descending)
+            } else {
+                quote!(::ordered_varint::__private::ElementReader::decode)
+            };
+            Ok(quote!(#function(&mut source)?))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    Ok(match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#names: #values),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#values),*)),
+        Fields::Unit => path,
+    })
+}
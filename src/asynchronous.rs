@@ -0,0 +1,301 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::task::{ready, Context, Poll};
+
+use crate::{io, Error, Variable};
+
+/// A buffered asynchronous source of bytes.
+pub trait BufferedSource {
+    /// Returns the bytes available from the source, reading more if none are
+    /// buffered. An empty slice means the source has ended.
+    fn poll_fill_buf(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>>;
+
+    /// Marks `amount` of the available bytes as read.
+    fn consume(&mut self, amount: usize);
+}
+
+/// The state of an in-progress asynchronous decode.
+///
+/// Rather than duplicating the decoding logic of each [`Variable`]
+/// implementation, values are decoded using [`Variable::decode_from_slice`],
+/// directly from the source's buffer when it contains the whole value. When
+/// the value is incomplete, the available bytes are copied and consumed, and
+/// decoding resumes once the [`Error::Truncated`] length has been read. Only
+/// the bytes of the value are consumed, and the copy only grows with the bytes
+/// actually read, so a corrupt length can't cause more memory to be allocated
+/// than the input contains.
+#[derive(Debug)]
+pub struct DecodeState<T> {
+    buffer: Vec<u8>,
+    /// The number of bytes needed before the buffer is decoded again.
+    needed: usize,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for DecodeState<T> {
+    fn default() -> Self {
+        Self {
+            buffer: Vec::new(),
+            needed: 1,
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<T: Variable> DecodeState<T> {
+    /// Reads from `source` until a value is decoded. Returns `None` if the
+    /// source ended before any bytes of the value were read.
+    pub fn poll_decode(
+        &mut self,
+        cx: &mut Context<'_>,
+        source: &mut impl BufferedSource,
+    ) -> Poll<Result<Option<T>, Error>> {
+        loop {
+            let previously_read = self.buffer.len();
+            let available = match ready!(source.poll_fill_buf(cx)) {
+                Ok(available) => available,
+                Err(err) => {
+                    self.reset();
+                    return Poll::Ready(Err(Error::Io(err)));
+                }
+            };
+            let read = available.len();
+            if read == 0 {
+                let result = if previously_read == 0 {
+                    Ok(None)
+                } else {
+                    Err(Error::Truncated {
+                        needed: self.needed,
+                    })
+                };
+                self.reset();
+                return Poll::Ready(result);
+            }
+
+            let result = if previously_read == 0 {
+                let result = T::decode_from_slice(available);
+                if matches!(result, Err(Error::Truncated { .. })) {
+                    self.buffer.extend_from_slice(available);
+                }
+                result
+            } else {
+                self.buffer.extend_from_slice(available);
+                if self.buffer.len() < self.needed {
+                    Err(Error::Truncated {
+                        needed: self.needed,
+                    })
+                } else {
+                    T::decode_from_slice(&self.buffer)
+                }
+            };
+
+            match result {
+                Ok((value, length)) => {
+                    source.consume(length - previously_read);
+                    self.reset();
+                    return Poll::Ready(Ok(Some(value)));
+                }
+                // Every available byte is part of the value.
+                Err(Error::Truncated { needed }) => {
+                    source.consume(read);
+                    self.needed = needed;
+                }
+                Err(err) => {
+                    self.reset();
+                    return Poll::Ready(Err(err));
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.needed = 1;
+    }
+}
+
+/// The state of an in-progress asynchronous encode.
+#[derive(Debug)]
+pub struct EncodeState {
+    encoded: Result<Vec<u8>, Option<Error>>,
+    written: usize,
+}
+
+impl EncodeState {
+    /// Encodes `value`. Any error is returned from the first call to
+    /// [`Self::poll_encode`].
+    pub fn new<T: Variable>(value: &T) -> Self {
+        Self {
+            encoded: value.to_variable_vec().map_err(Some),
+            written: 0,
+        }
+    }
+
+    /// Writes the encoded value using `poll_write`, returning the number of
+    /// bytes written.
+    pub fn poll_encode(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut poll_write: impl FnMut(&mut Context<'_>, &[u8]) -> Poll<io::Result<usize>>,
+    ) -> Poll<Result<usize, Error>> {
+        let encoded = match &mut self.encoded {
+            Ok(encoded) => &*encoded,
+            Err(err) => {
+                return Poll::Ready(Err(err.take().expect("polled after completion")));
+            }
+        };
+        while self.written < encoded.len() {
+            match ready!(poll_write(cx, &encoded[self.written..])) {
+                Ok(0) => {
+                    return Poll::Ready(Err(Error::Io(io::Error::from(io::ErrorKind::WriteZero))))
+                }
+                Ok(written) => self.written += written,
+                Err(err) => return Poll::Ready(Err(Error::Io(err))),
+            }
+        }
+        Poll::Ready(Ok(encoded.len()))
+    }
+}
+
+/// Implements the extension traits, futures and stream for the
+/// `AsyncBufRead` and `AsyncWrite` traits in scope of the invoking module.
+macro_rules! impl_async_variable {
+    () => {
+        /// Decodes [`Variable`]($crate::Variable) values from an
+        /// [`AsyncBufRead`] implementation.
+        pub trait AsyncReadVariableExt: AsyncBufRead + Unpin {
+            /// Decodes a variable length value from this reader.
+            ///
+            /// Only the bytes that make up the value are consumed. If the
+            /// reader ends before the value is fully read,
+            /// [`Error::Truncated`]($crate::Error::Truncated) is returned.
+            fn read_variable<T: $crate::Variable>(&mut self) -> ReadVariable<'_, Self, T> {
+                ReadVariable {
+                    reader: self,
+                    state: $crate::asynchronous::DecodeState::default(),
+                }
+            }
+        }
+
+        impl<R: AsyncBufRead + Unpin + ?Sized> AsyncReadVariableExt for R {}
+
+        /// Encodes [`Variable`]($crate::Variable) values into an
+        /// [`AsyncWrite`] implementation.
+        pub trait AsyncWriteVariableExt: AsyncWrite + Unpin {
+            /// Encodes `value` into this writer, returning the number of bytes
+            /// written upon success.
+            fn write_variable<T: $crate::Variable>(
+                &mut self,
+                value: &T,
+            ) -> WriteVariable<'_, Self> {
+                WriteVariable {
+                    writer: self,
+                    state: $crate::asynchronous::EncodeState::new(value),
+                }
+            }
+        }
+
+        impl<W: AsyncWrite + Unpin + ?Sized> AsyncWriteVariableExt for W {}
+
+        struct Buffered<'a, R: ?Sized>(&'a mut R);
+
+        impl<R: AsyncBufRead + Unpin + ?Sized> $crate::asynchronous::BufferedSource
+            for Buffered<'_, R>
+        {
+            fn poll_fill_buf(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<&[u8]>> {
+                Pin::new(&mut *self.0).poll_fill_buf(cx)
+            }
+
+            fn consume(&mut self, amount: usize) {
+                Pin::new(&mut *self.0).consume(amount);
+            }
+        }
+
+        /// A future that decodes a value. Returned from
+        /// [`AsyncReadVariableExt::read_variable`].
+        #[derive(Debug)]
+        #[must_use = "futures do nothing unless you `.await` or poll them"]
+        pub struct ReadVariable<'a, R: ?Sized, T> {
+            reader: &'a mut R,
+            state: $crate::asynchronous::DecodeState<T>,
+        }
+
+        impl<R: AsyncBufRead + Unpin + ?Sized, T: $crate::Variable> Future
+            for ReadVariable<'_, R, T>
+        {
+            type Output = Result<T, $crate::Error>;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                let this = self.get_mut();
+                let value = ready!(this.state.poll_decode(cx, &mut Buffered(this.reader)))?;
+                Poll::Ready(value.ok_or($crate::Error::Truncated { needed: 1 }))
+            }
+        }
+
+        /// A future that encodes a value. Returned from
+        /// [`AsyncWriteVariableExt::write_variable`].
+        #[derive(Debug)]
+        #[must_use = "futures do nothing unless you `.await` or poll them"]
+        pub struct WriteVariable<'a, W: ?Sized> {
+            writer: &'a mut W,
+            state: $crate::asynchronous::EncodeState,
+        }
+
+        impl<W: AsyncWrite + Unpin + ?Sized> Future for WriteVariable<'_, W> {
+            type Output = Result<usize, $crate::Error>;
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                let this = self.get_mut();
+                this.state.poll_encode(cx, |cx, bytes| {
+                    Pin::new(&mut *this.writer).poll_write(cx, bytes)
+                })
+            }
+        }
+
+        /// A [`Stream`] of values decoded from an [`AsyncBufRead`]
+        /// implementation.
+        ///
+        /// Only the bytes of the decoded values are consumed. The stream ends
+        /// when the reader ends between two values. If the reader ends partway
+        /// through a value or an error occurs, the error is returned and the
+        /// stream ends.
+        #[derive(Debug)]
+        pub struct VariableStream<R, T> {
+            reader: R,
+            state: $crate::asynchronous::DecodeState<T>,
+            finished: bool,
+        }
+
+        impl<R, T> VariableStream<R, T> {
+            /// Returns a stream that decodes values from `reader`.
+            pub fn new(reader: R) -> Self {
+                Self {
+                    reader,
+                    state: $crate::asynchronous::DecodeState::default(),
+                    finished: false,
+                }
+            }
+
+            /// Returns the underlying reader.
+            pub fn into_inner(self) -> R {
+                self.reader
+            }
+        }
+
+        impl<R: AsyncBufRead + Unpin, T: $crate::Variable> Stream for VariableStream<R, T> {
+            type Item = Result<T, $crate::Error>;
+
+            fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+                let this = self.get_mut();
+                if this.finished {
+                    return Poll::Ready(None);
+                }
+                let result = ready!(this.state.poll_decode(cx, &mut Buffered(&mut this.reader)));
+                this.finished = !matches!(result, Ok(Some(_)));
+                Poll::Ready(result.transpose())
+            }
+        }
+    };
+}
+
+pub(crate) use impl_async_variable;
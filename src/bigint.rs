@@ -0,0 +1,204 @@
+use alloc::vec::Vec;
+
+use num_bigint::{BigInt, BigUint, Sign};
+
+use crate::error::read_exact;
+use crate::io::{Read, Write};
+use crate::{Error, Signed, Unsigned, Variable};
+
+/// An arbitrarily large unsigned integer value.
+///
+/// Values that fit in a `u128` are encoded identically to [`Unsigned`]. Larger
+/// values use the same extended encoding as [`Unsigned`], whose length is
+/// itself encoded as an [`Unsigned`]. This allows encoded `BigUnsigned`,
+/// `Unsigned` and unsigned primitive values to be compared with each other.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, Ord, PartialOrd)]
+pub struct BigUnsigned(BigUint);
+
+impl BigUnsigned {
+    /// Returns true if `encoded` contains exactly one value that is encoded
+    /// using its shortest possible encoding.
+    #[must_use]
+    pub fn is_canonical(mut encoded: &[u8]) -> bool {
+        Self::decode(&mut encoded, true).is_ok() && encoded.is_empty()
+    }
+
+    /// Decodes a variable length value from `source`, returning
+    /// [`Error::NonCanonical`] if the value was not encoded using its shortest
+    /// possible encoding.
+    pub fn decode_canonical<R: Read>(source: R) -> Result<Self, Error> {
+        Self::decode(source, true)
+    }
+
+    fn encode<W: Write>(value: &BigUint, output: W) -> Result<usize, Error> {
+        if let Ok(value) = u128::try_from(value) {
+            Unsigned::encode_be_bytes(value.to_be_bytes(), output)
+        } else {
+            Unsigned::encode_extended(&value.to_bytes_be(), false, output)
+        }
+    }
+
+    fn decode<R: Read>(mut input: R, canonical: bool) -> Result<Self, Error> {
+        let mut first_byte = [0];
+        read_exact(&mut input, &mut first_byte, 1)?;
+        let short =
+            Unsigned::decode_remaining_bytes::<_, 16>(first_byte[0], &mut input, canonical)?;
+        if !Unsigned::is_extended_prefix(first_byte[0], &short) {
+            return Ok(Self(BigUint::from_bytes_be(&short)));
+        }
+
+        let data = read_extended_data(input, false, canonical)?;
+        if canonical && !Unsigned::is_canonical_extended(&data) {
+            return Err(Error::NonCanonical);
+        }
+        Ok(Self(BigUint::from_bytes_be(&data)))
+    }
+}
+
+impl Variable for BigUnsigned {
+    fn encode_variable<W: Write>(&self, output: W) -> Result<usize, Error> {
+        Self::encode(&self.0, output)
+    }
+
+    fn decode_variable<R: Read>(input: R) -> Result<Self, Error> {
+        Self::decode(input, false)
+    }
+}
+
+/// An arbitrarily large signed integer value.
+///
+/// Values that fit in an `i128` are encoded identically to [`Signed`]. Larger
+/// values use the same extended encoding as [`Signed`], whose length is itself
+/// encoded as an [`Unsigned`]. This allows encoded `BigSigned`, `Signed` and
+/// signed primitive values to be compared with each other.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, Ord, PartialOrd)]
+pub struct BigSigned(BigInt);
+
+impl BigSigned {
+    /// Returns true if `encoded` contains exactly one value that is encoded
+    /// using its shortest possible encoding.
+    #[must_use]
+    pub fn is_canonical(mut encoded: &[u8]) -> bool {
+        Self::decode(&mut encoded, true).is_ok() && encoded.is_empty()
+    }
+
+    /// Decodes a variable length value from `source`, returning
+    /// [`Error::NonCanonical`] if the value was not encoded using its shortest
+    /// possible encoding.
+    pub fn decode_canonical<R: Read>(source: R) -> Result<Self, Error> {
+        Self::decode(source, true)
+    }
+
+    fn encode<W: Write>(value: &BigInt, output: W) -> Result<usize, Error> {
+        if let Ok(value) = i128::try_from(value) {
+            Signed::encode_be_bytes(value.to_be_bytes(), output)
+        } else {
+            Unsigned::encode_extended(
+                &value.to_signed_bytes_be(),
+                value.sign() == Sign::Minus,
+                output,
+            )
+        }
+    }
+
+    fn decode<R: Read>(mut input: R, canonical: bool) -> Result<Self, Error> {
+        let mut first_byte = [0];
+        read_exact(&mut input, &mut first_byte, 1)?;
+        let short = Signed::decode_remaining_bytes::<_, 16>(first_byte[0], &mut input, canonical)?;
+        let Some(negative) = Signed::extended_prefix_sign(first_byte[0], &short) else {
+            return Ok(Self(BigInt::from(i128::from_be_bytes(short))));
+        };
+
+        let data = read_extended_data(input, negative, canonical)?;
+        // The sign of the two's complement data must agree with the prefix,
+        // otherwise the value doesn't fit in the data, matching `Signed`.
+        if (data[0] >> 7 == 1) != negative {
+            return Err(Error::Overflow {
+                target_bits: u32::try_from(data.len() * 8).unwrap_or(u32::MAX),
+            });
+        }
+        if canonical && !Signed::is_canonical_extended(&data) {
+            return Err(Error::NonCanonical);
+        }
+        Ok(Self(BigInt::from_signed_bytes_be(&data)))
+    }
+}
+
+impl Variable for BigSigned {
+    fn encode_variable<W: Write>(&self, output: W) -> Result<usize, Error> {
+        Self::encode(&self.0, output)
+    }
+
+    fn decode_variable<R: Read>(input: R) -> Result<Self, Error> {
+        Self::decode(input, false)
+    }
+}
+
+/// Reads the length and big-endian bytes of an extended encoding, after the
+/// prefix has already been read.
+fn read_extended_data<R: Read>(
+    mut input: R,
+    inverted: bool,
+    canonical: bool,
+) -> Result<Vec<u8>, Error> {
+    let (extra_bytes, length_bytes) =
+        Unsigned::decode_extended_length(&mut input, inverted, canonical)?;
+    let length = usize::try_from(extra_bytes)
+        .ok()
+        .and_then(|extra_bytes| extra_bytes.checked_add(Unsigned::EXTENDED_PREFIX_LEN))
+        .ok_or(Error::Overflow {
+            target_bits: usize::BITS,
+        })?;
+    let needed = length.saturating_add(Unsigned::EXTENDED_PREFIX_LEN + length_bytes);
+
+    // Read the data in chunks so that a corrupt length can't allocate more
+    // memory than the input actually contains.
+    let mut data = Vec::new();
+    while data.len() < length {
+        let start = data.len();
+        data.resize(start + (length - start).min(4096), 0);
+        read_exact(&mut input, &mut data[start..], needed)?;
+    }
+    Ok(data)
+}
+
+macro_rules! impl_bigint_variable {
+    ($ty:ty, $wrapper:ident) => {
+        impl Variable for $ty {
+            fn encode_variable<W: Write>(&self, output: W) -> Result<usize, Error> {
+                $wrapper::encode(self, output)
+            }
+
+            fn decode_variable<R: Read>(input: R) -> Result<Self, Error> {
+                $wrapper::decode_variable(input).map(Self::from)
+            }
+        }
+
+        impl From<$ty> for $wrapper {
+            fn from(value: $ty) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$wrapper> for $ty {
+            fn from(value: $wrapper) -> Self {
+                value.0
+            }
+        }
+    };
+}
+
+impl_bigint_variable!(BigUint, BigUnsigned);
+impl_bigint_variable!(BigInt, BigSigned);
+
+impl From<Unsigned> for BigUnsigned {
+    fn from(value: Unsigned) -> Self {
+        Self(BigUint::from(value.0))
+    }
+}
+
+impl From<Signed> for BigSigned {
+    fn from(value: Signed) -> Self {
+        Self(BigInt::from(i128::from(value)))
+    }
+}
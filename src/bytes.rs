@@ -0,0 +1,165 @@
+//! Encoding and decoding using [`bytes`]'s [`Buf`] and [`BufMut`]
+//! traits.
+//!
+//! ```rust
+//! use bytes::BytesMut;
+//! use ordered_varint::bytes::{BufMutVariableExt, BufVariableExt};
+//!
+//! let mut buffer = BytesMut::new();
+//! buffer.put_u64_ordered(300).unwrap();
+//! buffer.put_i32_ordered(-1).unwrap();
+//! assert_eq!(buffer.get_u64_ordered().unwrap(), 300);
+//! assert_eq!(buffer.get_i32_ordered().unwrap(), -1);
+//! ```
+
+use ::bytes::{Buf, BufMut};
+
+use crate::io::{self, Read, Write};
+use crate::{Error, Signed, Unsigned, Variable};
+
+macro_rules! put_methods {
+    ($($name:ident: $ty:ty),+ $(,)?) => {
+        $(
+            #[doc = concat!("Encodes `value` as an ordered `", stringify!($ty), "`, returning the number of bytes written.")]
+            ///
+            /// If `self` can't hold the encoded value,
+            /// [`Error::BufferTooSmall`] is returned.
+            fn $name(&mut self, value: $ty) -> Result<usize, Error> {
+                self.put_variable(&value)
+            }
+        )+
+    };
+}
+
+macro_rules! get_methods {
+    ($($name:ident: $ty:ty),+ $(,)?) => {
+        $(
+            #[doc = concat!("Decodes an ordered `", stringify!($ty), "`.")]
+            ///
+            /// The value may span multiple chunks. If an error is returned,
+            /// `self` may have been advanced past some of the value's bytes.
+            fn $name(&mut self) -> Result<$ty, Error> {
+                self.get_variable()
+            }
+        )+
+    };
+}
+
+/// Encodes [`Variable`] values into a [`BufMut`] implementation.
+pub trait BufMutVariableExt: BufMut {
+    /// Encodes `value`, returning the number of bytes written.
+    ///
+    /// If `self` can't hold the encoded value, [`Error::BufferTooSmall`] is
+    /// returned.
+    fn put_variable<T: Variable>(&mut self, value: &T) -> Result<usize, Error> {
+        value
+            .encode_variable(BufWriter(self))
+            .map_err(|err| match err {
+                Error::Io(err) if err.kind() == io::ErrorKind::WriteZero => Error::BufferTooSmall,
+                other => other,
+            })
+    }
+
+    put_methods!(
+        put_unsigned_ordered: Unsigned,
+        put_u8_ordered: u8,
+        put_u16_ordered: u16,
+        put_u32_ordered: u32,
+        put_u64_ordered: u64,
+        put_u128_ordered: u128,
+        put_usize_ordered: usize,
+        put_signed_ordered: Signed,
+        put_i8_ordered: i8,
+        put_i16_ordered: i16,
+        put_i32_ordered: i32,
+        put_i64_ordered: i64,
+        put_i128_ordered: i128,
+        put_isize_ordered: isize,
+    );
+}
+
+impl<B: BufMut + ?Sized> BufMutVariableExt for B {}
+
+/// Decodes [`Variable`] values from a [`Buf`] implementation.
+pub trait BufVariableExt: Buf {
+    /// Decodes a value.
+    ///
+    /// The value may span multiple chunks. If `self` ends before the value is
+    /// fully read, [`Error::Truncated`] is returned. If an error is returned,
+    /// `self` may have been advanced past some of the value's bytes.
+    fn get_variable<T: Variable>(&mut self) -> Result<T, Error> {
+        T::decode_variable(BufReader(self))
+    }
+
+    get_methods!(
+        get_unsigned_ordered: Unsigned,
+        get_u8_ordered: u8,
+        get_u16_ordered: u16,
+        get_u32_ordered: u32,
+        get_u64_ordered: u64,
+        get_u128_ordered: u128,
+        get_usize_ordered: usize,
+        get_signed_ordered: Signed,
+        get_i8_ordered: i8,
+        get_i16_ordered: i16,
+        get_i32_ordered: i32,
+        get_i64_ordered: i64,
+        get_i128_ordered: i128,
+        get_isize_ordered: isize,
+    );
+}
+
+impl<B: Buf + ?Sized> BufVariableExt for B {}
+
+/// Adapts a [`BufMut`] to [`Write`] without ever growing past its
+/// [`BufMut::remaining_mut`].
+struct BufWriter<'a, B: ?Sized>(&'a mut B);
+
+#[cfg(feature = "std")]
+impl<B: BufMut + ?Sized> Write for BufWriter<'_, B> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let length = bytes.len().min(self.0.remaining_mut());
+        self.0.put_slice(&bytes[..length]);
+        Ok(length)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<B: BufMut + ?Sized> Write for BufWriter<'_, B> {
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if bytes.len() > self.0.remaining_mut() {
+            return Err(io::Error::from(io::ErrorKind::WriteZero));
+        }
+        self.0.put_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Adapts a [`Buf`] to [`Read`], copying across chunk boundaries as needed.
+struct BufReader<'a, B: ?Sized>(&'a mut B);
+
+#[cfg(feature = "std")]
+impl<B: Buf + ?Sized> Read for BufReader<'_, B> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let length = buffer.len().min(self.0.remaining());
+        self.0.copy_to_slice(&mut buffer[..length]);
+        Ok(length)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<B: Buf + ?Sized> Read for BufReader<'_, B> {
+    fn read_exact(&mut self, buffer: &mut [u8]) -> io::Result<()> {
+        if buffer.len() > self.0.remaining() {
+            let remaining = self.0.remaining();
+            self.0.advance(remaining);
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        self.0.copy_to_slice(buffer);
+        Ok(())
+    }
+}
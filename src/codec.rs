@@ -0,0 +1,258 @@
+//! [`tokio_util::codec`] implementations for frames that are prefixed by
+//! their length encoded as an [`Unsigned`](crate::Unsigned) value.
+//!
+//! ```rust
+//! use futures::{SinkExt, StreamExt};
+//! use ordered_varint::codec::LengthDelimitedCodec;
+//! use tokio_util::codec::Framed;
+//!
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! let (client, server) = tokio::io::duplex(64);
+//! let mut client = Framed::new(client, LengthDelimitedCodec::new());
+//! let mut server = Framed::new(server, LengthDelimitedCodec::new());
+//! client.send(&b"hello"[..]).await.unwrap();
+//! assert_eq!(&server.next().await.unwrap().unwrap()[..], b"hello");
+//! # });
+//! ```
+
+use core::marker::PhantomData;
+
+use ::bytes::{Buf, Bytes, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::bytes::BufMutVariableExt;
+use crate::{Error, Variable};
+
+/// The default maximum payload length of a frame: 8 MiB.
+pub const DEFAULT_MAX_FRAME_LENGTH: usize = 8 * 1024 * 1024;
+
+/// The maximum encoded length of a frame's header: 4 KiB.
+///
+/// Headers whose type has a smaller [`Variable::MAX_ENCODED_LEN`] are limited
+/// to that length instead.
+pub const MAX_HEADER_LENGTH: usize = 4 * 1024;
+
+/// A codec for frames consisting of a header, the payload's length encoded as
+/// an [`Unsigned`](crate::Unsigned) value, and the payload.
+///
+/// The header can be any [`Variable`] type, such as a message type or a tuple
+/// of a message type and a request id. Decoded frames are returned as a tuple
+/// of the header and payload.
+#[derive(Debug)]
+pub struct FrameCodec<H> {
+    max_frame_length: usize,
+    _header: PhantomData<fn() -> H>,
+}
+
+impl<H> FrameCodec<H> {
+    /// Returns a codec that accepts payloads up to
+    /// [`DEFAULT_MAX_FRAME_LENGTH`] bytes long.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_max_frame_length(DEFAULT_MAX_FRAME_LENGTH)
+    }
+
+    /// Returns a codec that accepts payloads up to `max_frame_length` bytes
+    /// long.
+    #[must_use]
+    pub const fn with_max_frame_length(max_frame_length: usize) -> Self {
+        Self {
+            max_frame_length,
+            _header: PhantomData,
+        }
+    }
+
+    /// Returns the maximum payload length accepted by this codec.
+    #[must_use]
+    pub const fn max_frame_length(&self) -> usize {
+        self.max_frame_length
+    }
+
+    /// Sets the maximum payload length accepted by this codec. Encoding or
+    /// decoding a longer payload returns [`Error::FrameTooLarge`].
+    pub const fn set_max_frame_length(&mut self, max_frame_length: usize) {
+        self.max_frame_length = max_frame_length;
+    }
+
+    /// Returns [`Error::FrameTooLarge`] if a header of `length` bytes is
+    /// longer than a header of type `H` can be.
+    fn check_header_length(length: usize) -> Result<(), Error>
+    where
+        H: Variable,
+    {
+        let max = H::MAX_ENCODED_LEN.min(MAX_HEADER_LENGTH);
+        if length > max {
+            return Err(Error::FrameTooLarge {
+                length: length as u64,
+                max,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<H> Default for FrameCodec<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H> Clone for FrameCodec<H> {
+    fn clone(&self) -> Self {
+        Self::with_max_frame_length(self.max_frame_length)
+    }
+}
+
+impl<H: Variable> Decoder for FrameCodec<H> {
+    type Item = (H, BytesMut);
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Error> {
+        let mut remaining = &src[..];
+        let header = match H::take_from_slice(&mut remaining) {
+            Ok(header) => header,
+            Err(Error::Truncated { needed }) => {
+                // `needed` comes from the input, so it is checked before any
+                // space is reserved for it.
+                Self::check_header_length(needed)?;
+                src.reserve(needed.saturating_sub(src.len()));
+                return Ok(None);
+            }
+            Err(err) => return Err(err),
+        };
+        let header_length = src.len() - remaining.len();
+        Self::check_header_length(header_length)?;
+        Ok(decode_payload(src, header_length, self.max_frame_length)?
+            .map(|payload| (header, payload)))
+    }
+}
+
+impl<H: Variable> Encoder<(H, &[u8])> for FrameCodec<H> {
+    type Error = Error;
+
+    fn encode(&mut self, (header, payload): (H, &[u8]), dst: &mut BytesMut) -> Result<(), Error> {
+        let length = check_length(payload.len() as u64, self.max_frame_length)?;
+        Self::check_header_length(header.encoded_len())?;
+        dst.put_variable(&header)?;
+        dst.put_variable(&length)?;
+        dst.extend_from_slice(payload);
+        Ok(())
+    }
+}
+
+impl<H: Variable> Encoder<(H, Bytes)> for FrameCodec<H> {
+    type Error = Error;
+
+    fn encode(&mut self, (header, payload): (H, Bytes), dst: &mut BytesMut) -> Result<(), Error> {
+        self.encode((header, &payload[..]), dst)
+    }
+}
+
+/// A codec for frames consisting of the payload's length encoded as an
+/// [`Unsigned`](crate::Unsigned) value, followed by the payload.
+#[derive(Debug, Clone)]
+pub struct LengthDelimitedCodec {
+    max_frame_length: usize,
+}
+
+impl LengthDelimitedCodec {
+    /// Returns a codec that accepts payloads up to
+    /// [`DEFAULT_MAX_FRAME_LENGTH`] bytes long.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_max_frame_length(DEFAULT_MAX_FRAME_LENGTH)
+    }
+
+    /// Returns a codec that accepts payloads up to `max_frame_length` bytes
+    /// long.
+    #[must_use]
+    pub const fn with_max_frame_length(max_frame_length: usize) -> Self {
+        Self { max_frame_length }
+    }
+
+    /// Returns the maximum payload length accepted by this codec.
+    #[must_use]
+    pub const fn max_frame_length(&self) -> usize {
+        self.max_frame_length
+    }
+
+    /// Sets the maximum payload length accepted by this codec. Encoding or
+    /// decoding a longer payload returns [`Error::FrameTooLarge`].
+    pub const fn set_max_frame_length(&mut self, max_frame_length: usize) {
+        self.max_frame_length = max_frame_length;
+    }
+}
+
+impl Default for LengthDelimitedCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder for LengthDelimitedCodec {
+    type Item = BytesMut;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Error> {
+        decode_payload(src, 0, self.max_frame_length)
+    }
+}
+
+impl Encoder<&[u8]> for LengthDelimitedCodec {
+    type Error = Error;
+
+    fn encode(&mut self, payload: &[u8], dst: &mut BytesMut) -> Result<(), Error> {
+        let length = check_length(payload.len() as u64, self.max_frame_length)?;
+        dst.put_variable(&length)?;
+        dst.extend_from_slice(payload);
+        Ok(())
+    }
+}
+
+impl Encoder<Bytes> for LengthDelimitedCodec {
+    type Error = Error;
+
+    fn encode(&mut self, payload: Bytes, dst: &mut BytesMut) -> Result<(), Error> {
+        self.encode(&payload[..], dst)
+    }
+}
+
+/// Returns [`Error::FrameTooLarge`] if a payload of `length` bytes is longer
+/// than `max_frame_length`.
+fn check_length(length: u64, max_frame_length: usize) -> Result<usize, Error> {
+    usize::try_from(length)
+        .ok()
+        .filter(|&length| length <= max_frame_length)
+        .ok_or(Error::FrameTooLarge {
+            length,
+            max: max_frame_length,
+        })
+}
+
+/// Decodes the payload length and payload that follow the first
+/// `header_length` bytes of `src`, removing the whole frame from `src` once
+/// it has been fully received.
+fn decode_payload(
+    src: &mut BytesMut,
+    header_length: usize,
+    max_frame_length: usize,
+) -> Result<Option<BytesMut>, Error> {
+    let mut remaining = &src[header_length..];
+    let length = match u64::take_from_slice(&mut remaining) {
+        Ok(length) => check_length(length, max_frame_length)?,
+        Err(Error::Truncated { needed }) => {
+            src.reserve((header_length + needed).saturating_sub(src.len()));
+            return Ok(None);
+        }
+        Err(err) => return Err(err),
+    };
+
+    let prefix_length = src.len() - remaining.len();
+    let frame_length = prefix_length + length;
+    if src.len() < frame_length {
+        src.reserve(frame_length - src.len());
+        return Ok(None);
+    }
+    src.advance(prefix_length);
+    Ok(Some(src.split_to(length)))
+}
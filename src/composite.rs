@@ -0,0 +1,147 @@
+use crate::io::{self, Read, Write};
+use crate::reverse::decode_descending;
+use crate::{Error, Variable};
+
+macro_rules! impl_tuple_variable {
+    ($($name:ident: $index:tt),+) => {
+        impl<$($name: Variable),+> Variable for ($($name,)+) {
+            const MAX_ENCODED_LEN: usize = 0_usize $(.saturating_add($name::MAX_ENCODED_LEN))+;
+
+            fn encode_variable<W: Write>(&self, mut destination: W) -> Result<usize, Error> {
+                let mut total = 0;
+                $(total += self.$index.encode_variable(&mut destination)?;)+
+                Ok(total)
+            }
+
+            fn decode_variable<R: Read>(source: R) -> Result<Self, Error> {
+                let mut source = ElementReader::new(source);
+                Ok(($(source.decode::<$name>()?,)+))
+            }
+
+            fn encoded_len(&self) -> usize {
+                0 $(+ self.$index.encoded_len())+
+            }
+
+            fn encoded_len_in_slice(source: &[u8]) -> Result<usize, Error> {
+                let mut total = 0;
+                $(total += element_len_in_slice::<$name>(source, total)?;)+
+                Ok(total)
+            }
+        }
+    };
+}
+
+impl_tuple_variable!(T0: 0);
+impl_tuple_variable!(T0: 0, T1: 1);
+impl_tuple_variable!(T0: 0, T1: 1, T2: 2);
+impl_tuple_variable!(T0: 0, T1: 1, T2: 2, T3: 3);
+impl_tuple_variable!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4);
+impl_tuple_variable!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5);
+impl_tuple_variable!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6);
+impl_tuple_variable!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6, T7: 7);
+impl_tuple_variable!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6, T7: 7, T8: 8);
+impl_tuple_variable!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6, T7: 7, T8: 8, T9: 9);
+impl_tuple_variable!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6, T7: 7, T8: 8, T9: 9, T10: 10);
+impl_tuple_variable!(T0: 0, T1: 1, T2: 2, T3: 3, T4: 4, T5: 5, T6: 6, T7: 7, T8: 8, T9: 9, T10: 10, T11: 11);
+
+impl<T: Variable, const N: usize> Variable for [T; N] {
+    const MAX_ENCODED_LEN: usize = T::MAX_ENCODED_LEN.saturating_mul(N);
+
+    fn encode_variable<W: Write>(&self, mut destination: W) -> Result<usize, Error> {
+        let mut total = 0;
+        for value in self {
+            total += value.encode_variable(&mut destination)?;
+        }
+        Ok(total)
+    }
+
+    fn decode_variable<R: Read>(source: R) -> Result<Self, Error> {
+        let mut source = ElementReader::new(source);
+        let mut result = Ok(());
+        let values: [Option<T>; N] = core::array::from_fn(|_| {
+            result.as_ref().ok()?;
+            source.decode::<T>().map_err(|err| result = Err(err)).ok()
+        });
+        result?;
+        Ok(values.map(|value| value.expect("every element was decoded")))
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.iter().map(Variable::encoded_len).sum()
+    }
+
+    fn encoded_len_in_slice(source: &[u8]) -> Result<usize, Error> {
+        let mut total = 0;
+        for _ in 0..N {
+            total += element_len_in_slice::<T>(source, total)?;
+        }
+        Ok(total)
+    }
+}
+
+/// Returns the length of the element encoded at `offset` in `source`,
+/// reporting [`Error::Truncated`] relative to the start of `source`.
+fn element_len_in_slice<T: Variable>(source: &[u8], offset: usize) -> Result<usize, Error> {
+    T::encoded_len_in_slice(&source[offset..]).map_err(|err| offset_truncated(err, offset))
+}
+
+/// Adds `offset` to the number of bytes needed by an [`Error::Truncated`]
+/// reported by an element that starts `offset` bytes into a value.
+#[allow(clippy::missing_const_for_fn)] // `Error` can't be dropped in const fns with `std`.
+fn offset_truncated(err: Error, offset: usize) -> Error {
+    match err {
+        Error::Truncated { needed } => Error::Truncated {
+            needed: offset + needed,
+        },
+        other => other,
+    }
+}
+
+/// Decodes the elements of a composite value one after another.
+///
+/// [`Error::Truncated`] is reported relative to the start of the value rather
+/// than the start of the element being decoded. Used by tuples, arrays and
+/// `#[derive(Variable)]`.
+pub struct ElementReader<R> {
+    source: R,
+    offset: usize,
+}
+
+impl<R: Read> ElementReader<R> {
+    /// Returns a reader for the elements of the value at the start of
+    /// `source`.
+    pub const fn new(source: R) -> Self {
+        Self { source, offset: 0 }
+    }
+
+    /// Decodes the next element.
+    pub fn decode<T: Variable>(&mut self) -> Result<T, Error> {
+        let offset = self.offset;
+        T::decode_variable(&mut *self).map_err(|err| offset_truncated(err, offset))
+    }
+
+    /// Decodes the next element, which was written with every byte inverted
+    /// by [`encode_descending`](crate::reverse::encode_descending).
+    pub fn decode_descending<T: Variable>(&mut self) -> Result<T, Error> {
+        let offset = self.offset;
+        decode_descending(&mut *self).map_err(|err| offset_truncated(err, offset))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Read for ElementReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let read = self.source.read(buffer)?;
+        self.offset += read;
+        Ok(read)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<R: Read> Read for ElementReader<R> {
+    fn read_exact(&mut self, buffer: &mut [u8]) -> io::Result<()> {
+        self.source.read_exact(buffer)?;
+        self.offset += buffer.len();
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{Signed, Unsigned, Variable};
+
+/// Returns the first `N` bytes of `encoded`.
+const fn truncate<const N: usize>(encoded: [u8; Unsigned::max_encoded_len(16)]) -> [u8; N] {
+    let mut truncated = [0; N];
+    let mut index = 0;
+    while index < N {
+        truncated[index] = encoded[index];
+        index += 1;
+    }
+    truncated
+}
+
+macro_rules! const_encoders {
+    ($encode:path, $wide:ty, $($name:ident: $ty:ident),+) => {
+        $(
+            #[doc = concat!("Encodes a `", stringify!($ty), "` in a `const` context.")]
+            ///
+            /// Returns a buffer whose first bytes are the encoded value, and the
+            /// number of encoded bytes. The encoded bytes are identical to those
+            /// written by [`Variable::encode_variable`]. To encode a value as a
+            /// `&'static [u8]`, use [`ordered_varint!`](crate::ordered_varint!).
+            #[must_use]
+            pub const fn $name(value: $ty) -> ([u8; <$ty as Variable>::MAX_ENCODED_LEN], usize) {
+                let (encoded, length) = $encode(value as $wide);
+                (truncate(encoded), length)
+            }
+        )+
+    };
+}
+
+const_encoders!(
+    Unsigned::encode_const,
+    u128,
+    const_encode_u8: u8,
+    const_encode_u16: u16,
+    const_encode_u32: u32,
+    const_encode_u64: u64,
+    const_encode_u128: u128,
+    const_encode_usize: usize
+);
+const_encoders!(
+    Signed::encode_const,
+    i128,
+    const_encode_i8: i8,
+    const_encode_i16: i16,
+    const_encode_i32: i32,
+    const_encode_i64: i64,
+    const_encode_i128: i128,
+    const_encode_isize: isize
+);
+
+/// Encodes an integer at compile time, expanding to a `&'static [u8]`.
+///
+/// The integer's type is written before the value, which can be any constant
+/// expression of that type. The bytes are identical to those written by
+/// [`Variable::encode_variable`].
+///
+/// ```rust
+/// use ordered_varint::{ordered_varint, Variable};
+///
+/// const USERS_TABLE: &[u8] = ordered_varint!(u32: 300);
+/// const OFFSET: &[u8] = ordered_varint!(i64: -1);
+///
+/// assert_eq!(USERS_TABLE, &*300_u32.to_variable_array::<5>().unwrap());
+/// assert_eq!(OFFSET, [0x7F]);
+/// ```
+#[macro_export]
+macro_rules! ordered_varint {
+    (u8: $value:expr) => {
+        $crate::__ordered_varint!(u8, const_encode_u8, $value)
+    };
+    (u16: $value:expr) => {
+        $crate::__ordered_varint!(u16, const_encode_u16, $value)
+    };
+    (u32: $value:expr) => {
+        $crate::__ordered_varint!(u32, const_encode_u32, $value)
+    };
+    (u64: $value:expr) => {
+        $crate::__ordered_varint!(u64, const_encode_u64, $value)
+    };
+    (u128: $value:expr) => {
+        $crate::__ordered_varint!(u128, const_encode_u128, $value)
+    };
+    (usize: $value:expr) => {
+        $crate::__ordered_varint!(usize, const_encode_usize, $value)
+    };
+    (i8: $value:expr) => {
+        $crate::__ordered_varint!(i8, const_encode_i8, $value)
+    };
+    (i16: $value:expr) => {
+        $crate::__ordered_varint!(i16, const_encode_i16, $value)
+    };
+    (i32: $value:expr) => {
+        $crate::__ordered_varint!(i32, const_encode_i32, $value)
+    };
+    (i64: $value:expr) => {
+        $crate::__ordered_varint!(i64, const_encode_i64, $value)
+    };
+    (i128: $value:expr) => {
+        $crate::__ordered_varint!(i128, const_encode_i128, $value)
+    };
+    (isize: $value:expr) => {
+        $crate::__ordered_varint!(isize, const_encode_isize, $value)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ordered_varint {
+    ($ty:ident, $encode:ident, $value:expr) => {{
+        const ENCODED: ([u8; <$ty as $crate::Variable>::MAX_ENCODED_LEN], usize) =
+            $crate::$encode($value);
+        const BUFFER: &[u8] = &ENCODED.0;
+        const BYTES: &[u8] = BUFFER.split_at(ENCODED.1).0;
+        BYTES
+    }};
+}
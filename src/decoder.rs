@@ -0,0 +1,150 @@
+use core::fmt::{self, Debug};
+
+use crate::{Error, Signed, Unsigned, Variable};
+
+/// The longest encoding of an [`Unsigned`] or [`Signed`] value: the extended
+/// prefix, a one byte length, and 16 big-endian bytes.
+const MAX_LENGTH: usize = Unsigned::EXTENDED_PREFIX_LEN + 1 + 16;
+
+/// The progress of a push-based decoder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeStatus<T> {
+    /// At least this many more bytes are needed to decode the value.
+    NeedMore(usize),
+    /// The value was decoded. The second field is the number of bytes consumed
+    /// from the input passed to the final call to `push`. The remaining bytes
+    /// belong to whatever follows the value.
+    Done(T, usize),
+}
+
+impl<T> DecodeStatus<T> {
+    /// Converts the decoded value using `map`.
+    pub fn map<U>(self, map: impl FnOnce(T) -> U) -> DecodeStatus<U> {
+        match self {
+            Self::NeedMore(needed) => DecodeStatus::NeedMore(needed),
+            Self::Done(value, consumed) => DecodeStatus::Done(map(value), consumed),
+        }
+    }
+}
+
+/// Buffers the bytes of a single value until it can be decoded.
+///
+/// Rather than duplicating the header parsing of [`Unsigned`] and [`Signed`],
+/// the bytes received so far are decoded using
+/// [`Variable::decode_from_slice`]. When the value is incomplete, the
+/// [`Error::Truncated`] it returns reports the total length known from the
+/// header, which is how many bytes are accepted before decoding again. This
+/// ensures that bytes beyond the end of the value are never consumed.
+#[derive(Clone)]
+struct PushDecoder {
+    buffer: [u8; MAX_LENGTH],
+    filled: usize,
+    needed: usize,
+}
+
+impl PushDecoder {
+    const fn new() -> Self {
+        Self {
+            buffer: [0; MAX_LENGTH],
+            filled: 0,
+            needed: 1,
+        }
+    }
+
+    fn push<T: Variable>(&mut self, mut input: &[u8]) -> Result<DecodeStatus<T>, Error> {
+        let mut consumed = 0;
+        loop {
+            let length = (self.needed - self.filled).min(input.len());
+            self.buffer[self.filled..self.filled + length].copy_from_slice(&input[..length]);
+            self.filled += length;
+            consumed += length;
+            input = &input[length..];
+            if self.filled < self.needed {
+                return Ok(DecodeStatus::NeedMore(self.needed - self.filled));
+            }
+
+            match T::decode_from_slice(&self.buffer[..self.filled]) {
+                Ok((value, _)) => {
+                    self.reset();
+                    return Ok(DecodeStatus::Done(value, consumed));
+                }
+                Err(Error::Truncated { needed }) if needed <= MAX_LENGTH => {
+                    self.needed = needed;
+                }
+                Err(err) => {
+                    self.reset();
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    const fn reset(&mut self) {
+        self.filled = 0;
+        self.needed = 1;
+    }
+}
+
+macro_rules! impl_decoder {
+    ($name:ident, $ty:ident) => {
+        #[doc = concat!("A resumable decoder for [`", stringify!($ty), "`] values that arrive in arbitrary chunks.")]
+        ///
+        /// Bytes are passed to [`Self::push`] as they are received. Only the
+        /// bytes that belong to the value are consumed, the value's bytes are
+        /// buffered inline, and the decoder never allocates. After a value is
+        /// decoded or an error is returned, the decoder is ready to decode
+        /// another value.
+        #[derive(Clone)]
+        pub struct $name(PushDecoder);
+
+        impl $name {
+            /// Returns a decoder that hasn't received any bytes.
+            #[must_use]
+            pub const fn new() -> Self {
+                Self(PushDecoder::new())
+            }
+
+            /// Consumes bytes from the start of `input`, returning the decoded
+            /// value once all of its bytes have been received.
+            ///
+            /// While the value is incomplete, all of `input` is consumed and
+            /// [`DecodeStatus::NeedMore`] reports the minimum number of
+            /// additional bytes required. Once the first byte has been
+            /// received, this is the exact number of bytes remaining for all
+            /// values except those using the extended encoding.
+            pub fn push(&mut self, input: &[u8]) -> Result<DecodeStatus<$ty>, Error> {
+                self.0.push(input)
+            }
+
+            /// Returns the number of bytes of the current value received so
+            /// far.
+            #[must_use]
+            pub const fn buffered(&self) -> usize {
+                self.0.filled
+            }
+
+            /// Discards any bytes of the current value received so far.
+            pub const fn reset(&mut self) {
+                self.0.reset();
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("buffered", &&self.0.buffer[..self.0.filled])
+                    .field("needed", &self.0.needed)
+                    .finish()
+            }
+        }
+    };
+}
+
+impl_decoder!(UnsignedDecoder, Unsigned);
+impl_decoder!(SignedDecoder, Signed);
@@ -0,0 +1,191 @@
+//! Encoding and decoding using [`embedded_io`]'s blocking
+//! [`Read`] and [`Write`] traits.
+//!
+//! Values are encoded identically to [`Variable::encode_variable`], so values
+//! written by a device can be decoded using the rest of this crate's API.
+//!
+//! ```rust
+//! use ordered_varint::embedded_io::{ReadVariableExt, WriteVariableExt};
+//!
+//! let mut buffer = [0; 16];
+//! let mut writer = &mut buffer[..];
+//! writer.write_variable(&1_700_000_000_u64).unwrap();
+//!
+//! let mut reader = &buffer[..];
+//! assert_eq!(reader.read_variable::<u64>().unwrap(), 1_700_000_000);
+//! ```
+
+use core::fmt::{self, Display};
+
+use ::embedded_io::{ErrorKind, ErrorType, Read, Write};
+
+use crate::{io, Variable};
+
+/// An error from encoding or decoding a [`Variable`] value using an
+/// [`embedded_io`] device.
+#[derive(Debug)]
+pub enum Error<E> {
+    /// The device returned an error.
+    Device(E),
+    /// The value could not be encoded or decoded.
+    Variable(crate::Error),
+}
+
+impl<E: fmt::Debug> Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Device(err) => write!(f, "device error: {err:?}"),
+            Self::Variable(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl<E: fmt::Debug> core::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Device(_) => None,
+            Self::Variable(err) => Some(err),
+        }
+    }
+}
+
+impl<E: ::embedded_io::Error> ::embedded_io::Error for Error<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Device(err) => err.kind(),
+            Self::Variable(crate::Error::Truncated { .. }) => ErrorKind::Other,
+            Self::Variable(_) => ErrorKind::InvalidData,
+        }
+    }
+}
+
+impl<E> From<crate::Error> for Error<E> {
+    fn from(err: crate::Error) -> Self {
+        Self::Variable(err)
+    }
+}
+
+/// Decodes [`Variable`] values from an [`embedded_io::Read`]
+/// implementation.
+pub trait ReadVariableExt: Read {
+    /// Decodes a variable length value from this device.
+    ///
+    /// If the device ends before the value is fully read,
+    /// [`crate::Error::Truncated`] is returned.
+    fn read_variable<T: Variable>(&mut self) -> Result<T, Error<Self::Error>> {
+        let mut reader = Device::new(self);
+        let result = T::decode_variable(&mut reader);
+        reader.finish(result)
+    }
+}
+
+impl<R: Read + ?Sized> ReadVariableExt for R {}
+
+/// Encodes [`Variable`] values into an [`embedded_io::Write`]
+/// implementation.
+pub trait WriteVariableExt: Write {
+    /// Encodes `value` into this device, returning the number of bytes written
+    /// upon success.
+    fn write_variable<T: Variable>(&mut self, value: &T) -> Result<usize, Error<Self::Error>> {
+        let mut writer = Device::new(self);
+        let result = value.encode_variable(&mut writer);
+        writer.finish(result)
+    }
+}
+
+impl<W: Write + ?Sized> WriteVariableExt for W {}
+
+/// Adapts an [`embedded_io`] device to this crate's I/O traits.
+///
+/// Device errors can't be stored in an [`io::Error`], so the first one is kept
+/// here and reported by [`Self::finish`] in place of the error returned by the
+/// [`Variable`] implementation.
+struct Device<'a, D: ErrorType + ?Sized> {
+    device: &'a mut D,
+    error: Option<D::Error>,
+}
+
+impl<'a, D: ErrorType + ?Sized> Device<'a, D> {
+    const fn new(device: &'a mut D) -> Self {
+        Self {
+            device,
+            error: None,
+        }
+    }
+
+    fn store_error(&mut self, err: D::Error) -> io::Error {
+        self.error = Some(err);
+        io::Error::from(io::ErrorKind::InvalidData)
+    }
+
+    fn finish<T>(self, result: Result<T, crate::Error>) -> Result<T, Error<D::Error>> {
+        match (result, self.error) {
+            (Ok(value), _) => Ok(value),
+            (Err(_), Some(err)) => Err(Error::Device(err)),
+            (Err(err), None) => Err(Error::Variable(err)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: Read + ?Sized> io::Read for Device<'_, D> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.device
+            .read(buffer)
+            .map_err(|err| self.store_error(err))
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<D: Read + ?Sized> io::Read for Device<'_, D> {
+    fn read_exact(&mut self, buffer: &mut [u8]) -> io::Result<()> {
+        self.device.read_exact(buffer).map_err(|err| match err {
+            ::embedded_io::ReadExactError::UnexpectedEof => {
+                io::Error::from(io::ErrorKind::UnexpectedEof)
+            }
+            ::embedded_io::ReadExactError::Other(err) => self.store_error(err),
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: Write + ?Sized> io::Write for Device<'_, D> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.device
+            .write(bytes)
+            .map_err(|err| self.store_error(err))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.device.flush().map_err(|err| self.store_error(err))
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<D: Write + ?Sized> io::Write for Device<'_, D> {
+    fn write_all(&mut self, mut bytes: &[u8]) -> io::Result<()> {
+        while !bytes.is_empty() {
+            match self.device.write(bytes) {
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::WriteZero)),
+                Ok(written) => bytes = &bytes[written..],
+                Err(err) => return Err(self.store_error(err)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Converts the error from reading the bytes of a value, `needed` bytes into
+/// the value, into an [`Error`].
+#[cfg(feature = "embedded-io-async")]
+pub(crate) fn read_exact_error<E>(
+    err: ::embedded_io::ReadExactError<E>,
+    needed: usize,
+) -> Error<E> {
+    match err {
+        ::embedded_io::ReadExactError::UnexpectedEof => {
+            Error::Variable(crate::Error::Truncated { needed })
+        }
+        ::embedded_io::ReadExactError::Other(err) => Error::Device(err),
+    }
+}
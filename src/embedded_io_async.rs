@@ -0,0 +1,141 @@
+//! Encoding and decoding using [`embedded_io_async`]'s
+//! [`Read`] and [`Write`] traits.
+//!
+//! Values are encoded identically to [`Variable::encode_variable`], and errors
+//! are reported using [`embedded_io::Error`](crate::embedded_io::Error).
+//!
+//! Decoding doesn't allocate. Instead, the bytes of a value are read into a
+//! stack buffer of [`MAX_ASYNC_DECODE_LEN`] bytes, and longer values can't be
+//! decoded.
+
+use ::embedded_io_async::{Read, Write};
+
+use crate::embedded_io::{read_exact_error, Error};
+use crate::io;
+use crate::Variable;
+
+/// The maximum encoded length of a value that can be decoded by
+/// [`AsyncReadVariableExt::read_variable`]. Every integer type supported by
+/// this crate fits.
+pub const MAX_ASYNC_DECODE_LEN: usize = 64;
+
+/// Decodes [`Variable`] values from an
+/// [`embedded_io_async::Read`] implementation.
+#[allow(async_fn_in_trait)]
+pub trait AsyncReadVariableExt: Read {
+    /// Decodes a variable length value from this device.
+    ///
+    /// Only the bytes that make up the value are read. If the device ends
+    /// before the value is fully read, [`crate::Error::Truncated`] is
+    /// returned. If the value is longer than [`MAX_ASYNC_DECODE_LEN`] bytes,
+    /// [`crate::Error::BufferTooSmall`] is returned.
+    async fn read_variable<T: Variable>(&mut self) -> Result<T, Error<Self::Error>> {
+        let mut buffer = [0; MAX_ASYNC_DECODE_LEN];
+        let mut filled = 0;
+        loop {
+            match T::decode_from_slice(&buffer[..filled]) {
+                Ok((value, _)) => return Ok(value),
+                Err(crate::Error::Truncated { needed }) => {
+                    let Some(unfilled) = buffer.get_mut(filled..needed) else {
+                        return Err(Error::Variable(crate::Error::BufferTooSmall));
+                    };
+                    self.read_exact(unfilled)
+                        .await
+                        .map_err(|err| read_exact_error(err, needed))?;
+                    filled = needed;
+                }
+                Err(err) => return Err(Error::Variable(err)),
+            }
+        }
+    }
+}
+
+impl<R: Read + ?Sized> AsyncReadVariableExt for R {}
+
+/// Encodes [`Variable`] values into an [`embedded_io_async::Write`]
+/// implementation.
+#[allow(async_fn_in_trait)]
+pub trait AsyncWriteVariableExt: Write {
+    /// Encodes `value` into this device, returning the number of bytes written
+    /// upon success.
+    ///
+    /// The value is encoded into a stack buffer in chunks, so values of any
+    /// length can be written without allocating.
+    async fn write_variable<T: Variable>(
+        &mut self,
+        value: &T,
+    ) -> Result<usize, Error<Self::Error>> {
+        let mut written = 0;
+        loop {
+            let mut window = Window::new(written);
+            let total = match value.encode_variable(&mut window) {
+                Ok(total) => Some(total),
+                Err(_) if window.full => None,
+                Err(err) => return Err(Error::Variable(err)),
+            };
+            self.write_all(&window.buffer[..window.filled])
+                .await
+                .map_err(Error::Device)?;
+            written += window.filled;
+            if let Some(total) = total {
+                return Ok(total);
+            }
+        }
+    }
+}
+
+impl<W: Write + ?Sized> AsyncWriteVariableExt for W {}
+
+/// Captures the encoded bytes following the first `skip` bytes, until its
+/// buffer is full.
+struct Window {
+    skip: usize,
+    buffer: [u8; MAX_ASYNC_DECODE_LEN],
+    filled: usize,
+    full: bool,
+}
+
+impl Window {
+    const fn new(skip: usize) -> Self {
+        Self {
+            skip,
+            buffer: [0; MAX_ASYNC_DECODE_LEN],
+            filled: 0,
+            full: false,
+        }
+    }
+
+    /// Consumes as much of `bytes` as possible, returning the number of bytes
+    /// consumed.
+    fn consume(&mut self, bytes: &[u8]) -> usize {
+        let skipped = self.skip.min(bytes.len());
+        self.skip -= skipped;
+        let remaining = &bytes[skipped..];
+        let copied = remaining.len().min(self.buffer.len() - self.filled);
+        self.buffer[self.filled..self.filled + copied].copy_from_slice(&remaining[..copied]);
+        self.filled += copied;
+        self.full = copied < remaining.len();
+        skipped + copied
+    }
+}
+
+#[cfg(feature = "std")]
+impl io::Write for Window {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        Ok(self.consume(bytes))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl io::Write for Window {
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.consume(bytes) < bytes.len() {
+            return Err(io::Error::from(io::ErrorKind::WriteZero));
+        }
+        Ok(())
+    }
+}
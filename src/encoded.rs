@@ -0,0 +1,78 @@
+use crate::{Error, Signed, Unsigned, Variable};
+
+macro_rules! impl_encoded {
+    ($name:ident, $ty:ident) => {
+        #[doc = concat!("A borrowed, validated encoding of a single [`", stringify!($ty), "`] value.")]
+        ///
+        /// The encoding is validated once when the view is created, after
+        /// which it can be compared, hashed and forwarded without decoding it.
+        /// Only canonical encodings are accepted, which ensures that comparing
+        /// two views orders them the same as their decoded values.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name<'a>(&'a [u8]);
+
+        #[allow(clippy::len_without_is_empty)] // Encoded values are never empty.
+        impl<'a> $name<'a> {
+            /// Validates the value encoded at the start of `source`, returning
+            /// a view of its bytes.
+            ///
+            /// If the value isn't canonically encoded,
+            /// [`Error::NonCanonical`] is returned. If `source` ends before the
+            /// value does, [`Error::Truncated`] is returned.
+            pub fn from_prefix(source: &'a [u8]) -> Result<Self, Error> {
+                let mut remaining = source;
+                $ty::decode_canonical(&mut remaining)?;
+                Ok(Self(&source[..source.len() - remaining.len()]))
+            }
+
+            /// Returns the encoded bytes.
+            #[must_use]
+            pub const fn as_bytes(&self) -> &'a [u8] {
+                self.0
+            }
+
+            /// Returns the number of encoded bytes.
+            #[must_use]
+            pub const fn len(&self) -> usize {
+                self.0.len()
+            }
+
+            /// Decodes the value as `T`.
+            ///
+            /// If the value doesn't fit in `T`, [`Error::Overflow`] is
+            /// returned.
+            pub fn decode<T: TryFrom<$ty>>(&self) -> Result<T, Error> {
+                let value = $ty::decode_variable(self.0)?;
+                T::try_from(value).map_err(|_| Error::Overflow {
+                    target_bits: core::mem::size_of::<T>() as u32 * 8,
+                })
+            }
+        }
+
+        impl AsRef<[u8]> for $name<'_> {
+            fn as_ref(&self) -> &[u8] {
+                self.0
+            }
+        }
+    };
+}
+
+impl_encoded!(EncodedUnsigned, Unsigned);
+impl_encoded!(EncodedSigned, Signed);
+
+impl EncodedUnsigned<'_> {
+    /// Returns false, because unsigned values are never negative. This mirrors
+    /// [`EncodedSigned::is_negative`] for code that handles both views.
+    #[must_use]
+    pub const fn is_negative(&self) -> bool {
+        false
+    }
+}
+
+impl EncodedSigned<'_> {
+    /// Returns true if the value is negative.
+    #[must_use]
+    pub const fn is_negative(&self) -> bool {
+        Signed::header_info(self.0[0]).0
+    }
+}
@@ -0,0 +1,140 @@
+use core::fmt::{self, Display};
+
+use crate::io::{self, Read};
+
+/// An error from encoding or decoding a [`Variable`](crate::Variable) value.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The value being encoded is outside of the range supported by the
+    /// encoding.
+    ValueOutOfRange,
+    /// The decoded value does not fit in the type being decoded.
+    Overflow {
+        /// The number of bits in the type being decoded.
+        target_bits: u32,
+    },
+    /// The header of the encoded value is invalid.
+    InvalidHeader,
+    /// The value was not encoded using its shortest possible encoding.
+    NonCanonical,
+    /// An escape sequence in encoded bytes or a string is invalid.
+    InvalidEscape,
+    /// A decoded string is not valid UTF-8.
+    InvalidUtf8,
+    /// A NaN was decoded into a type that does not allow NaN values.
+    NotANumber,
+    /// The decoded enum ordinal does not match any variant.
+    UnknownVariant {
+        /// The decoded ordinal.
+        ordinal: u64,
+    },
+    /// The input ended before the encoded value was fully read.
+    Truncated {
+        /// The minimum total number of bytes needed to decode the value.
+        needed: usize,
+    },
+    /// The destination is too small to hold the encoded value.
+    BufferTooSmall,
+    /// A frame's payload is longer than the codec's maximum frame length, or
+    /// its header is longer than
+    /// [`MAX_HEADER_LENGTH`](crate::codec::MAX_HEADER_LENGTH).
+    #[cfg(feature = "tokio-util")]
+    FrameTooLarge {
+        /// The length of the payload or header.
+        length: u64,
+        /// The maximum payload or header length accepted by the codec.
+        max: usize,
+    },
+    /// An error occurred while reading or writing.
+    Io(io::Error),
+    /// An error reported by a type's `Serialize` or `Deserialize`
+    /// implementation.
+    #[cfg(feature = "serde")]
+    Custom(alloc::string::String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ValueOutOfRange => f.write_str("value is out of the encodable range"),
+            Self::Overflow { target_bits } => {
+                write!(f, "decoded value does not fit in {target_bits} bits")
+            }
+            Self::InvalidHeader => f.write_str("invalid header"),
+            Self::NonCanonical => f.write_str("value is not canonically encoded"),
+            Self::InvalidEscape => f.write_str("invalid escape sequence"),
+            Self::InvalidUtf8 => f.write_str("decoded string is not valid utf-8"),
+            Self::NotANumber => f.write_str("decoded value is NaN"),
+            Self::UnknownVariant { ordinal } => write!(f, "unknown variant ordinal {ordinal}"),
+            Self::Truncated { needed } => {
+                write!(f, "input truncated: {needed} bytes are needed")
+            }
+            Self::BufferTooSmall => f.write_str("buffer too small"),
+            #[cfg(feature = "tokio-util")]
+            Self::FrameTooLarge { length, max } => {
+                write!(f, "frame length {length} exceeds the maximum of {max}")
+            }
+            Self::Io(err) => write!(f, "io error: {err}"),
+            #[cfg(feature = "serde")]
+            Self::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match err {
+            Error::Io(err) => return err,
+            Error::Truncated { .. } => io::ErrorKind::UnexpectedEof,
+            Error::BufferTooSmall => io::ErrorKind::WriteZero,
+            Error::ValueOutOfRange
+            | Error::Overflow { .. }
+            | Error::InvalidHeader
+            | Error::NonCanonical
+            | Error::InvalidEscape
+            | Error::InvalidUtf8
+            | Error::NotANumber
+            | Error::UnknownVariant { .. } => io::ErrorKind::InvalidData,
+            #[cfg(feature = "tokio-util")]
+            Error::FrameTooLarge { .. } => io::ErrorKind::InvalidData,
+            #[cfg(feature = "serde")]
+            Error::Custom(_) => io::ErrorKind::InvalidData,
+        };
+        #[cfg(feature = "std")]
+        {
+            Self::new(kind, err)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            Self::from(kind)
+        }
+    }
+}
+
+/// Reads exactly enough bytes to fill `buffer`, reporting an unexpected end of
+/// input as [`Error::Truncated`] with `needed` as the total length.
+pub fn read_exact<R: Read>(mut input: R, buffer: &mut [u8], needed: usize) -> Result<(), Error> {
+    input.read_exact(buffer).map_err(|err| {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            Error::Truncated { needed }
+        } else {
+            Error::Io(err)
+        }
+    })
+}
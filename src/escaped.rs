@@ -0,0 +1,132 @@
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::error::read_exact;
+#[cfg(feature = "alloc")]
+use crate::io::Read;
+use crate::io::Write;
+use crate::Error;
+#[cfg(feature = "alloc")]
+use crate::Variable;
+
+/// The byte that begins an escape sequence.
+const ESCAPE: u8 = 0x00;
+/// Follows [`ESCAPE`] to represent a `0x00` byte in the original data.
+const ESCAPED_ZERO: u8 = 0xFF;
+/// Follows [`ESCAPE`] to mark the end of the encoded bytes.
+const TERMINATOR: u8 = 0x01;
+
+/// Encodes `bytes` into `destination` using an order-preserving, terminated
+/// encoding, returning the number of bytes written.
+///
+/// Each `0x00` byte is written as `0x00 0xFF`, and the end of the bytes is
+/// marked with `0x00 0x01`. The encoded bytes sort in the same order as the
+/// original bytes, and because the encoding is prefix-free, it can be followed
+/// by other encoded values, such as in a tuple.
+///
+/// `Variable` is implemented using this encoding for `Vec<u8>`, `String`,
+/// `Cow<'_, [u8]>` and `Cow<'_, str>` when the `alloc` feature is enabled.
+pub fn encode_bytes<W: Write>(bytes: &[u8], mut destination: W) -> Result<usize, Error> {
+    let mut written = 0;
+    for chunk in bytes.split_inclusive(|&byte| byte == ESCAPE) {
+        destination.write_all(chunk)?;
+        written += chunk.len();
+        if chunk.last() == Some(&ESCAPE) {
+            destination.write_all(&[ESCAPED_ZERO])?;
+            written += 1;
+        }
+    }
+    destination.write_all(&[ESCAPE, TERMINATOR])?;
+    Ok(written + 2)
+}
+
+/// Encodes `string` into `destination` using the same encoding as
+/// [`encode_bytes`], returning the number of bytes written.
+///
+/// Encoded strings sort in the same order as [`str`]'s [`Ord`]
+/// implementation.
+pub fn encode_str<W: Write>(string: &str, destination: W) -> Result<usize, Error> {
+    encode_bytes(string.as_bytes(), destination)
+}
+
+/// Decodes bytes encoded with [`encode_bytes`] from `source`.
+#[cfg(feature = "alloc")]
+fn decode_vec<R: Read>(mut source: R) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    // The number of encoded bytes read so far.
+    let mut read = 0;
+    loop {
+        let mut byte = [0];
+        read_exact(&mut source, &mut byte, read + 2)?;
+        read += 1;
+        if byte[0] == ESCAPE {
+            read_exact(&mut source, &mut byte, read + 1)?;
+            read += 1;
+            match byte[0] {
+                ESCAPED_ZERO => bytes.push(0),
+                TERMINATOR => return Ok(bytes),
+                _ => return Err(Error::InvalidEscape),
+            }
+        } else {
+            bytes.push(byte[0]);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn decode_string<R: Read>(source: R) -> Result<String, Error> {
+    String::from_utf8(decode_vec(source)?).map_err(|_| Error::InvalidUtf8)
+}
+
+#[cfg(feature = "alloc")]
+impl Variable for Vec<u8> {
+    fn encode_variable<W: Write>(&self, destination: W) -> Result<usize, Error> {
+        encode_bytes(self, destination)
+    }
+
+    fn decode_variable<R: Read>(source: R) -> Result<Self, Error> {
+        decode_vec(source)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Variable for String {
+    fn encode_variable<W: Write>(&self, destination: W) -> Result<usize, Error> {
+        encode_str(self, destination)
+    }
+
+    fn decode_variable<R: Read>(source: R) -> Result<Self, Error> {
+        decode_string(source)
+    }
+}
+
+/// Allows borrowed bytes to be encoded as part of a composite key. Decoding
+/// always produces [`Cow::Owned`].
+#[cfg(feature = "alloc")]
+impl Variable for Cow<'_, [u8]> {
+    fn encode_variable<W: Write>(&self, destination: W) -> Result<usize, Error> {
+        encode_bytes(self, destination)
+    }
+
+    fn decode_variable<R: Read>(source: R) -> Result<Self, Error> {
+        decode_vec(source).map(Cow::Owned)
+    }
+}
+
+/// Allows a borrowed string to be encoded as part of a composite key. Decoding
+/// always produces [`Cow::Owned`].
+#[cfg(feature = "alloc")]
+impl Variable for Cow<'_, str> {
+    fn encode_variable<W: Write>(&self, destination: W) -> Result<usize, Error> {
+        encode_str(self, destination)
+    }
+
+    fn decode_variable<R: Read>(source: R) -> Result<Self, Error> {
+        decode_string(source).map(Cow::Owned)
+    }
+}
@@ -0,0 +1,115 @@
+use crate::error::read_exact;
+use crate::io::{Read, Write};
+use crate::{Error, Variable};
+
+macro_rules! impl_float_variable {
+    ($ty:ty, $bits:ty) => {
+        /// Floating point values are encoded as the big-endian bytes of their
+        /// bits, transformed so that the encoded bytes sort in the order
+        /// defined by `total_cmp`:
+        ///
+        /// - Positive values have their sign bit set.
+        /// - Negative values have all of their bits inverted.
+        ///
+        /// This encoding is always 4 bytes for `f32` and 8 bytes for `f64`.
+        /// `-0.0` and `+0.0` are encoded differently, and `-0.0` sorts before
+        /// `+0.0`. NaN values keep their payload: NaNs with the sign bit set
+        /// sort before negative infinity, and all other NaNs sort after
+        /// positive infinity.
+        impl Variable for $ty {
+            const MAX_ENCODED_LEN: usize = core::mem::size_of::<$bits>();
+
+            fn encode_variable<W: Write>(&self, mut destination: W) -> Result<usize, Error> {
+                const SIGN: $bits = 1 << (<$bits>::BITS - 1);
+                let bits = self.to_bits();
+                let ordered = if bits & SIGN == 0 { bits | SIGN } else { !bits };
+                let bytes = ordered.to_be_bytes();
+                destination.write_all(&bytes)?;
+                Ok(bytes.len())
+            }
+
+            fn decode_variable<R: Read>(mut source: R) -> Result<Self, Error> {
+                const SIGN: $bits = 1 << (<$bits>::BITS - 1);
+                const LENGTH: usize = core::mem::size_of::<$bits>();
+                let mut bytes = [0; LENGTH];
+                read_exact(&mut source, &mut bytes, LENGTH)?;
+                let ordered = <$bits>::from_be_bytes(bytes);
+                let bits = if ordered & SIGN == 0 {
+                    !ordered
+                } else {
+                    ordered & !SIGN
+                };
+                Ok(<$ty>::from_bits(bits))
+            }
+
+            fn encoded_len(&self) -> usize {
+                Self::MAX_ENCODED_LEN
+            }
+        }
+    };
+}
+
+impl_float_variable!(f32, u32);
+impl_float_variable!(f64, u64);
+
+#[cfg(feature = "ordered-float")]
+macro_rules! impl_ordered_float_variable {
+    ($ty:ty, $quiet_nan:literal, $canonical:ident) => {
+        /// Returns the value that represents every value equal to `value` in
+        /// `OrderedFloat` and `NotNan`.
+        fn $canonical(value: $ty) -> $ty {
+            if value.is_nan() {
+                <$ty>::from_bits($quiet_nan)
+            } else if value == 0.0 {
+                0.0
+            } else {
+                value
+            }
+        }
+
+        /// Uses the same encoding as the wrapped float, except that `-0.0` is
+        /// encoded as `+0.0` and every NaN is encoded as the positive quiet
+        /// NaN. This matches the `Eq` and `Ord` implementations of
+        /// `OrderedFloat`, which consider `-0.0` and `+0.0` equal, and every
+        /// NaN equal and greater than all other values.
+        impl Variable for ordered_float::OrderedFloat<$ty> {
+            const MAX_ENCODED_LEN: usize = <$ty>::MAX_ENCODED_LEN;
+
+            fn encode_variable<W: Write>(&self, destination: W) -> Result<usize, Error> {
+                $canonical(self.0).encode_variable(destination)
+            }
+
+            fn decode_variable<R: Read>(source: R) -> Result<Self, Error> {
+                <$ty>::decode_variable(source).map(Self)
+            }
+
+            fn encoded_len(&self) -> usize {
+                Self::MAX_ENCODED_LEN
+            }
+        }
+
+        /// Uses the same encoding as the wrapped float, except that `-0.0` is
+        /// encoded as `+0.0`, matching the `Eq` and `Ord` implementations of
+        /// `NotNan`. Decoding a NaN returns [`Error::NotANumber`].
+        impl Variable for ordered_float::NotNan<$ty> {
+            const MAX_ENCODED_LEN: usize = <$ty>::MAX_ENCODED_LEN;
+
+            fn encode_variable<W: Write>(&self, destination: W) -> Result<usize, Error> {
+                $canonical(self.into_inner()).encode_variable(destination)
+            }
+
+            fn decode_variable<R: Read>(source: R) -> Result<Self, Error> {
+                Self::new(<$ty>::decode_variable(source)?).map_err(|_| Error::NotANumber)
+            }
+
+            fn encoded_len(&self) -> usize {
+                Self::MAX_ENCODED_LEN
+            }
+        }
+    };
+}
+
+#[cfg(feature = "ordered-float")]
+impl_ordered_float_variable!(f32, 0x7FC0_0000, canonical_f32);
+#[cfg(feature = "ordered-float")]
+impl_ordered_float_variable!(f64, 0x7FF8_0000_0000_0000, canonical_f64);
@@ -0,0 +1,25 @@
+//! Asynchronous encoding and decoding using the
+//! [`futures-io`](::futures_io) traits.
+//!
+//! ```rust
+//! use futures::io::Cursor;
+//! use ordered_varint::futures::{AsyncReadVariableExt, AsyncWriteVariableExt};
+//!
+//! # futures::executor::block_on(async {
+//! let mut buffer = Cursor::new(Vec::new());
+//! buffer.write_variable(&300_u64).await.unwrap();
+//! buffer.set_position(0);
+//! assert_eq!(buffer.read_variable::<u64>().await.unwrap(), 300);
+//! # });
+//! ```
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+
+use futures_core::Stream;
+use futures_io::{AsyncBufRead, AsyncWrite};
+
+use crate::asynchronous::impl_async_variable;
+
+impl_async_variable!();
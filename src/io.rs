@@ -0,0 +1,165 @@
+//! The I/O traits used to encode and decode values.
+//!
+//! When the `std` feature is enabled, this module re-exports the relevant types
+//! from `std::io`. Otherwise, it provides a minimal `core`-only subset of the
+//! same API, which is enough to encode into and decode from byte slices (and
+//! `Vec<u8>` when the `alloc` feature is enabled).
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, ErrorKind, Read, Result, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::core_io::*;
+
+#[cfg(not(feature = "std"))]
+mod core_io {
+    use core::fmt::{self, Display};
+
+    /// A specialized `Result` type for I/O operations.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// The general categories of I/O errors.
+    #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub enum ErrorKind {
+        /// The data being read was not valid.
+        InvalidData,
+        /// The source ran out of bytes before the value was fully read.
+        UnexpectedEof,
+        /// The destination could not accept all of the bytes written.
+        WriteZero,
+    }
+
+    /// An error that occurred while reading or writing bytes.
+    #[derive(Debug)]
+    pub struct Error {
+        kind: ErrorKind,
+    }
+
+    impl Error {
+        /// Returns the kind of error that occurred.
+        #[must_use]
+        pub const fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl From<ErrorKind> for Error {
+        fn from(kind: ErrorKind) -> Self {
+            Self { kind }
+        }
+    }
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.kind {
+                ErrorKind::InvalidData => f.write_str("invalid data"),
+                ErrorKind::UnexpectedEof => f.write_str("unexpected end of file"),
+                ErrorKind::WriteZero => f.write_str("failed to write whole buffer"),
+            }
+        }
+    }
+
+    impl core::error::Error for Error {}
+
+    /// A source of bytes.
+    pub trait Read {
+        /// Reads exactly enough bytes to fill `buffer`.
+        ///
+        /// If the source ends first, an error of kind
+        /// [`ErrorKind::UnexpectedEof`] is returned.
+        fn read_exact(&mut self, buffer: &mut [u8]) -> Result<()>;
+    }
+
+    /// A destination for bytes.
+    pub trait Write {
+        /// Writes all of `bytes`.
+        ///
+        /// If the destination can't accept all of the bytes, an error of kind
+        /// [`ErrorKind::WriteZero`] is returned.
+        fn write_all(&mut self, bytes: &[u8]) -> Result<()>;
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read_exact(&mut self, buffer: &mut [u8]) -> Result<()> {
+            (**self).read_exact(buffer)
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+            (**self).write_all(bytes)
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read_exact(&mut self, buffer: &mut [u8]) -> Result<()> {
+            if buffer.len() > self.len() {
+                *self = &self[self.len()..];
+                return Err(Error::from(ErrorKind::UnexpectedEof));
+            }
+            let (read, remaining) = self.split_at(buffer.len());
+            buffer.copy_from_slice(read);
+            *self = remaining;
+            Ok(())
+        }
+    }
+
+    impl Write for &mut [u8] {
+        fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+            if bytes.len() > self.len() {
+                return Err(Error::from(ErrorKind::WriteZero));
+            }
+            let (written, remaining) = core::mem::take(self).split_at_mut(bytes.len());
+            written.copy_from_slice(bytes);
+            *self = remaining;
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl Write for alloc::vec::Vec<u8> {
+        fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+            self.extend_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<R: Read + ?Sized> Read for alloc::boxed::Box<R> {
+        fn read_exact(&mut self, buffer: &mut [u8]) -> Result<()> {
+            (**self).read_exact(buffer)
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl<W: Write + ?Sized> Write for alloc::boxed::Box<W> {
+        fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+            (**self).write_all(bytes)
+        }
+    }
+}
+
+/// A [`Write`] implementation that counts the bytes written to it.
+#[derive(Debug, Default)]
+pub(crate) struct ByteCounter(pub usize);
+
+#[cfg(feature = "std")]
+impl Write for ByteCounter {
+    fn write(&mut self, bytes: &[u8]) -> Result<usize> {
+        self.0 += bytes.len();
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl Write for ByteCounter {
+    fn write_all(&mut self, bytes: &[u8]) -> Result<()> {
+        self.0 += bytes.len();
+        Ok(())
+    }
+}
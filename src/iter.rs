@@ -0,0 +1,211 @@
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use crate::{Error, Variable};
+
+/// An iterator over values encoded back to back in a byte slice.
+///
+/// Each item is the offset of the value within the slice, along with the
+/// decoded value. Because the encodings aren't self-synchronizing, iteration
+/// ends after the first error.
+///
+/// Values can also be iterated in reverse, and [`Self::count_remaining`]
+/// counts the values that haven't been iterated from either end.
+///
+/// ```rust
+/// use ordered_varint::{Variable, VariableIter};
+///
+/// let mut encoded = [0; 6];
+/// let mut unused = &mut encoded[..];
+/// for value in [1_u64, 300, 70_000] {
+///     value.encode_variable(&mut unused).unwrap();
+/// }
+///
+/// let mut values = VariableIter::<u64>::new(&encoded);
+/// assert_eq!(values.count_remaining().unwrap(), 3);
+/// let (offset, value) = values.nth(1).unwrap();
+/// assert_eq!((offset, value.unwrap()), (1, 300));
+/// let (offset, value) = values.next().unwrap();
+/// assert_eq!((offset, value.unwrap()), (3, 70_000));
+/// assert!(values.next().is_none());
+///
+/// let mut values = VariableIter::<u64>::new(&encoded);
+/// let (offset, value) = values.next_back().unwrap();
+/// assert_eq!((offset, value.unwrap()), (3, 70_000));
+/// assert_eq!(values.count_remaining().unwrap(), 2);
+/// ```
+#[derive(Debug)]
+pub struct VariableIter<'a, T> {
+    source: &'a [u8],
+    offset: usize,
+    /// The offset of the last value returned from the back.
+    end: usize,
+    finished: bool,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> VariableIter<'a, T> {
+    /// Returns an iterator over the values encoded in `source`.
+    #[must_use]
+    pub const fn new(source: &'a [u8]) -> Self {
+        Self {
+            source,
+            offset: 0,
+            end: source.len(),
+            finished: false,
+            _value: PhantomData,
+        }
+    }
+
+    /// Returns the offset of the next value.
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the bytes that haven't been iterated yet.
+    #[must_use]
+    pub fn remaining_bytes(&self) -> &'a [u8] {
+        if self.finished {
+            &[]
+        } else {
+            &self.source[self.offset..self.end]
+        }
+    }
+}
+
+impl<T> Clone for VariableIter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source,
+            offset: self.offset,
+            end: self.end,
+            finished: self.finished,
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<T: Variable> VariableIter<'_, T> {
+    /// Advances past the next value without decoding it, returning its offset.
+    ///
+    /// Integer types are skipped by reading only their header bytes. See
+    /// [`Variable::encoded_len_in_slice`].
+    fn skip_value(&mut self) -> Option<Result<usize, Error>> {
+        let remaining = self.remaining_bytes();
+        if remaining.is_empty() {
+            return None;
+        }
+        match T::encoded_len_in_slice(remaining) {
+            Ok(length) => {
+                let offset = self.offset;
+                self.offset += length;
+                self.finished = length == 0;
+                Some(Ok(offset))
+            }
+            Err(err) => {
+                self.finished = true;
+                Some(Err(err))
+            }
+        }
+    }
+
+    /// Counts the values that haven't been iterated from either end, without
+    /// decoding them or advancing the iterator.
+    ///
+    /// If a value is truncated or has an invalid header, the error is
+    /// returned. Like [`Iterator::nth`], integers are counted by reading only
+    /// their headers, so values that don't fit in `T` aren't detected.
+    pub fn count_remaining(&self) -> Result<usize, Error> {
+        let mut values = self.clone();
+        let mut count = 0;
+        while let Some(result) = values.skip_value() {
+            result?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+impl<T: Variable> Iterator for VariableIter<'_, T> {
+    type Item = (usize, Result<T, Error>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.remaining_bytes();
+        if remaining.is_empty() {
+            return None;
+        }
+        match T::decode_from_slice(remaining) {
+            Ok((value, length)) => {
+                let offset = self.offset;
+                self.offset += length;
+                // Values that encode to no bytes would otherwise repeat
+                // forever.
+                self.finished = length == 0;
+                Some((offset, Ok(value)))
+            }
+            Err(err) => {
+                self.finished = true;
+                Some((self.offset, Err(err)))
+            }
+        }
+    }
+
+    /// Returns bounds based on the number of remaining bytes. Use
+    /// [`VariableIter::count_remaining`] to count the remaining values.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // A corrupt value ends iteration after a single item, so only one
+        // item is guaranteed. Every value other than the last is at least one
+        // byte long.
+        let remaining = self.remaining_bytes().len();
+        (remaining.min(1), Some(remaining))
+    }
+
+    /// Skips `n` values by reading only their headers, then decodes the
+    /// following value.
+    ///
+    /// Skipped integers aren't checked to fit in `T`.
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        for _ in 0..n {
+            if let Err(err) = self.skip_value()? {
+                return Some((self.offset, Err(err)));
+            }
+        }
+        self.next()
+    }
+}
+
+impl<T: Variable> DoubleEndedIterator for VariableIter<'_, T> {
+    /// Returns the last remaining value.
+    ///
+    /// Encodings can only be read from the front, so the value is found by
+    /// skipping over the headers of every remaining value before it. Values
+    /// are returned in the reverse order that [`Iterator::next`] would return
+    /// them, so if a value has an invalid header, the error is returned first,
+    /// followed by the values before it.
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let mut values = self.clone();
+        let mut last = None;
+        loop {
+            let offset = values.offset;
+            match values.skip_value() {
+                Some(Ok(_)) => last = Some((offset, None)),
+                Some(Err(err)) => {
+                    last = Some((offset, Some(err)));
+                    break;
+                }
+                None => break,
+            }
+        }
+
+        let (offset, err) = last?;
+        let value = match err {
+            Some(err) => Err(err),
+            None => T::decode_from_slice(&self.source[offset..self.end]).map(|(value, _)| value),
+        };
+        self.end = offset;
+        Some((offset, value))
+    }
+}
+
+impl<T: Variable> FusedIterator for VariableIter<'_, T> {}
@@ -1,4 +1,5 @@
 #![doc= include_str!("../.rustme/docs.md")]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![forbid(unsafe_code)]
 #![warn(
     clippy::cargo,
@@ -18,39 +19,199 @@
     clippy::cast_possible_truncation,
 )]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+// Allows code generated by `#[derive(Variable)]` to be tested within this crate.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as ordered_varint;
+
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+mod asynchronous;
+#[cfg(feature = "num-bigint")]
+mod bigint;
+#[cfg(feature = "bytes")]
+pub mod bytes;
+#[cfg(feature = "tokio-util")]
+pub mod codec;
+mod composite;
+mod constant;
+mod decoder;
+#[cfg(feature = "embedded-io")]
+pub mod embedded_io;
+#[cfg(feature = "embedded-io-async")]
+pub mod embedded_io_async;
+mod encoded;
+mod error;
+mod escaped;
+mod float;
+#[cfg(feature = "futures-io")]
+pub mod futures;
+pub mod io;
+mod iter;
+mod ordered_bytes;
+mod reverse;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "serde")]
+pub mod serde_as;
 mod signed;
+#[cfg(feature = "tokio")]
+pub mod tokio;
 mod unsigned;
+mod validate;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::io::{Read, Write};
 
-use std::io::{Read, Write};
+#[cfg(feature = "num-bigint")]
+pub use self::bigint::*;
+#[cfg(feature = "derive")]
+pub use ordered_varint_derive::Variable;
 
+pub use self::constant::*;
+pub use self::decoder::*;
+pub use self::encoded::*;
+pub use self::error::Error;
+pub use self::escaped::*;
+pub use self::iter::*;
+pub use self::ordered_bytes::*;
 pub use self::signed::*;
 pub use self::unsigned::*;
+pub use self::validate::*;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::composite::ElementReader;
+    pub use crate::reverse::encode_descending;
+}
 
 /// Encodes and decodes a type using a variable-length format.
+///
+/// Encodings are prefix-free: no encoded value is the start of another encoded
+/// value of the same type. This allows values to be concatenated without a
+/// separator, which is how tuples of up to 12 elements and arrays `[T; N]` are
+/// encoded. Because each element's encoding preserves its order, concatenated
+/// encodings sort lexicographically, element by element, making tuples
+/// suitable as composite keys:
+///
+/// ```rust
+/// use ordered_varint::Variable;
+///
+/// let first = (1_u32, 2_u16, -5_i64).to_variable_array::<16>().unwrap();
+/// let second = (1_u32, 3_u16, -10_i64).to_variable_array::<16>().unwrap();
+/// assert!(first < second);
+/// assert_eq!(
+///     <(u32, u16, i64)>::decode_variable(&first[..]).unwrap(),
+///     (1, 2, -5)
+/// );
+/// ```
 pub trait Variable: Sized {
+    /// The maximum number of bytes any value of this type encodes to, or
+    /// `usize::MAX` if encoded values have no maximum length.
+    ///
+    /// This can be used to size stack buffers for encoding.
+    const MAX_ENCODED_LEN: usize = usize::MAX;
+
     /// Encodes `self` into `destination`, returning the number of bytes written upon success.
-    fn encode_variable<W: Write>(&self, destination: W) -> std::io::Result<usize>;
+    fn encode_variable<W: Write>(&self, destination: W) -> Result<usize, Error>;
     /// Decodes a variable length value from `source`.
-    fn decode_variable<R: Read>(source: R) -> std::io::Result<Self>;
+    fn decode_variable<R: Read>(source: R) -> Result<Self, Error>;
+
+    /// Encodes `self` into the start of `destination`, returning the number of
+    /// bytes written upon success.
+    ///
+    /// If `destination` is too small to hold the encoded value,
+    /// [`Error::BufferTooSmall`] is returned. The contents of `destination` are
+    /// unspecified when an error is returned.
+    fn encode_into_slice(&self, destination: &mut [u8]) -> Result<usize, Error> {
+        self.encode_variable(destination).map_err(|err| match err {
+            Error::Io(err) if err.kind() == io::ErrorKind::WriteZero => Error::BufferTooSmall,
+            other => other,
+        })
+    }
+
+    /// Decodes a variable length value from the start of `source`, returning
+    /// the value and the number of bytes read.
+    ///
+    /// If `source` ends before the value is fully read, [`Error::Truncated`] is
+    /// returned.
+    fn decode_from_slice(source: &[u8]) -> Result<(Self, usize), Error> {
+        let mut remaining = source;
+        let value = Self::take_from_slice(&mut remaining)?;
+        Ok((value, source.len() - remaining.len()))
+    }
+
+    /// Decodes a variable length value from the start of `source`, advancing
+    /// `source` past the bytes read.
+    ///
+    /// `source` is left unchanged if an error is returned.
+    fn take_from_slice(source: &mut &[u8]) -> Result<Self, Error> {
+        let mut remaining = *source;
+        let value = Self::decode_variable(&mut remaining)?;
+        *source = remaining;
+        Ok(value)
+    }
+
+    /// Returns the number of bytes `self` encodes to.
+    ///
+    /// Integer types compute the length directly from the value. Other types
+    /// may encode the value to count its bytes. If `self` can't be encoded,
+    /// the number of bytes written before the error occurred is returned.
+    fn encoded_len(&self) -> usize {
+        let mut counter = io::ByteCounter::default();
+        self.encode_variable(&mut counter).unwrap_or(counter.0)
+    }
+
+    /// Returns the length of the value encoded at the start of `source`.
+    ///
+    /// Integer types only read the header bytes needed to determine the
+    /// length, and don't check that the value fits in `Self`. Other types may
+    /// decode the value. If `source` ends before the value does,
+    /// [`Error::Truncated`] is returned.
+    fn encoded_len_in_slice(source: &[u8]) -> Result<usize, Error> {
+        Self::decode_from_slice(source).map(|(_, length)| length)
+    }
 
     /// Encodes `self` into a new `Vec<u8>`.
-    fn to_variable_vec(&self) -> std::io::Result<Vec<u8>> {
+    #[cfg(feature = "alloc")]
+    fn to_variable_vec(&self) -> Result<Vec<u8>, Error> {
         let mut output = Vec::with_capacity(16);
         self.encode_variable(&mut output)?;
         Ok(output)
     }
+
+    /// Encodes `self` into an [`OrderedBytes`] stored on the stack.
+    ///
+    /// If the encoded value is longer than `N` bytes,
+    /// [`Error::BufferTooSmall`] is returned. [`EncodedVariable`] can hold
+    /// any integer value.
+    fn to_variable_array<const N: usize>(&self) -> Result<OrderedBytes<N>, Error> {
+        OrderedBytes::new(self)
+    }
 }
 
 macro_rules! impl_primitive_variable {
     ($ty:ty,  $dest:ty) => {
         impl Variable for $ty {
-            fn encode_variable<W: Write>(&self, destination: W) -> std::io::Result<usize> {
+            const MAX_ENCODED_LEN: usize = Unsigned::max_encoded_len(core::mem::size_of::<$ty>());
+
+            fn encode_variable<W: Write>(&self, destination: W) -> Result<usize, Error> {
                 <$dest>::encode_be_bytes(self.to_be_bytes(), destination)
             }
 
-            fn decode_variable<R: Read>(source: R) -> std::io::Result<Self> {
+            fn encoded_len(&self) -> usize {
+                <$dest>::from(*self).encoded_len()
+            }
+
+            fn decode_variable<R: Read>(source: R) -> Result<Self, Error> {
                 <$dest>::decode_variable_bytes(source).map(<Self>::from_be_bytes)
             }
+
+            fn encoded_len_in_slice(source: &[u8]) -> Result<usize, Error> {
+                <$dest>::encoded_len_in_slice(source)
+            }
         }
     };
 }
@@ -69,7 +230,7 @@ impl_primitive_variable!(i64, Signed);
 impl_primitive_variable!(i128, Signed);
 impl_primitive_variable!(isize, Signed);
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use std::fmt::Debug;
 
@@ -139,6 +300,14 @@ mod tests {
             decoded, variable,
             "decoded value did not match: {variable:?} vs {decoded:?}",
         );
+
+        // The slice-based APIs must produce and consume the same bytes.
+        let mut buffer = [0; 33];
+        let encoded_length = value.encode_into_slice(&mut buffer).unwrap();
+        assert_eq!(&buffer[..encoded_length], &bytes[..]);
+        let (decoded, read) = T::decode_from_slice(&buffer).unwrap();
+        assert_eq!(decoded, value);
+        assert_eq!(read, expected_bytes);
     }
 
     #[test]
@@ -256,11 +425,13 @@ mod tests {
         roundtrip(2_u128.pow(116) - 1, 15);
         roundtrip(2_u128.pow(116), 16);
 
-        // Maximum value
-        roundtrip(2_u128.pow(124) - 1, 16);
+        // Maximum value without the extended encoding
+        roundtrip(2_u128.pow(124) - 2, 16);
 
-        // Above maximum value
-        assert!(2_u128.pow(124).encode_variable(&mut Vec::new()).is_err());
+        // Extended encoding
+        roundtrip(2_u128.pow(124) - 1, 33);
+        roundtrip(2_u128.pow(124), 33);
+        roundtrip(u128::MAX, 33);
     }
 
     #[test]
@@ -290,17 +461,89 @@ mod tests {
         roundtrip(2_i128.pow(115), 16);
         roundtrip(-(2_i128.pow(115) + 1), 16);
 
-        // Maximum value
-        roundtrip(2_i128.pow(123) - 1, 16);
-        // Minimum value
-        roundtrip(-(2_i128.pow(123)), 16);
+        // Maximum value without the extended encoding
+        roundtrip(2_i128.pow(123) - 2, 16);
+        // Minimum value without the extended encoding
+        roundtrip(-(2_i128.pow(123)) + 1, 16);
+
+        // Extended encoding
+        roundtrip(2_i128.pow(123) - 1, 33);
+        roundtrip(-(2_i128.pow(123)), 33);
+        roundtrip(i128::MAX, 33);
+        roundtrip(i128::MIN, 33);
+    }
+
+    fn assert_ordered<T: Variable + Ord + Copy + Debug>(mut values: Vec<T>) {
+        values.sort_unstable();
+        let encoded = values
+            .iter()
+            .map(|value| value.to_variable_vec().unwrap())
+            .collect::<Vec<_>>();
+        let mut sorted = encoded.clone();
+        sorted.sort();
+        assert_eq!(encoded, sorted);
+        for (value, encoded) in values.iter().zip(&encoded) {
+            assert_eq!(
+                T::decode_from_slice(encoded).unwrap(),
+                (*value, encoded.len())
+            );
+        }
+    }
+
+    #[test]
+    fn extended_encoding() {
+        // Existing encodings are unchanged, and the extended encoding is
+        // prefixed by the previous encoding of the boundary values.
+        assert_eq!(
+            (2_u128.pow(124) - 2).to_variable_vec().unwrap(),
+            [[0xFF; 15].as_slice(), &[0xFE]].concat()
+        );
+        assert_eq!(
+            u128::MAX.to_variable_vec().unwrap(),
+            [[0xFF; 16].as_slice(), &[0], &[0xFF; 16]].concat()
+        );
+        assert_eq!(
+            i128::MAX.to_variable_vec().unwrap(),
+            [[0xFF; 16].as_slice(), &[0], &[0x7F], &[0xFF; 15]].concat()
+        );
+        assert_eq!(
+            i128::MIN.to_variable_vec().unwrap(),
+            [[0; 16].as_slice(), &[0xFF], &[0x80], &[0; 15]].concat()
+        );
+
+        let mut unsigned = vec![0, 2_u128.pow(116), 2_u128.pow(120), u128::MAX];
+        unsigned.extend((0..=4).map(|offset| 2_u128.pow(124) - 3 + offset));
+        unsigned.extend((0..=4).map(|offset| u128::MAX - offset));
+        let mut signed = vec![0, i128::MIN, i128::MAX, 2_i128.pow(120), -(2_i128.pow(120))];
+        signed.extend((0..=4).map(|offset| 2_i128.pow(123) - 3 + offset));
+        signed.extend((0..=4).map(|offset| -(2_i128.pow(123)) - 2 + offset));
+        signed.extend((0..=4).map(|offset| i128::MIN + offset));
+        signed.extend((0..=4).map(|offset| i128::MAX - offset));
 
-        // Above maximum
-        assert!(2_i128.pow(123).encode_variable(&mut Vec::new()).is_err());
-        // Below minimum
-        assert!((-(2_i128.pow(123)) - 1)
-            .encode_variable(&mut Vec::new())
-            .is_err());
+        assert_ordered(unsigned);
+        assert_ordered(signed);
+
+        // Extended values never fit in smaller types.
+        let encoded = u128::MAX.to_variable_vec().unwrap();
+        assert!(matches!(
+            u64::decode_variable(&encoded[..]),
+            Err(Error::Overflow { target_bits: 64 })
+        ));
+        let encoded = i128::MIN.to_variable_vec().unwrap();
+        assert!(matches!(
+            i64::decode_variable(&encoded[..]),
+            Err(Error::Overflow { target_bits: 64 })
+        ));
+        assert!(matches!(
+            i128::decode_variable(&encoded[..20]),
+            Err(Error::Truncated { needed: 33 })
+        ));
+        // An extended length that doesn't fit in a u128.
+        let too_long = [[0xFF; 16].as_slice(), &[1], &[0xFF; 17]].concat();
+        assert!(matches!(
+            u128::decode_variable(&too_long[..]),
+            Err(Error::Overflow { target_bits: 128 })
+        ));
     }
 
     #[test]
@@ -315,6 +558,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unnecessary_fallible_conversions)] // `TryFrom` is tested on purpose.
     fn conversions() {
         assert_eq!(
             isize::try_from(Signed::from(isize::MAX)).unwrap(),
@@ -329,6 +573,10 @@ mod tests {
             u128::try_from(Unsigned::from(u128::MAX)).unwrap(),
             u128::MAX
         );
+
+        // The 128-bit conversions are also infallible.
+        assert_eq!(i128::from(Signed::from(i128::MAX)), i128::MAX);
+        assert_eq!(u128::from(Unsigned::from(u128::MAX)), u128::MAX);
     }
 
     #[test]
@@ -355,6 +603,127 @@ mod tests {
         assert_eq!(originals, entries);
     }
 
+    #[test]
+    fn slices() {
+        let mut buffer = [0; 4];
+        let mut remaining = &mut buffer[..];
+        let mut written = 0;
+        for value in [1_u32, 300, 0] {
+            let length = value.encode_into_slice(remaining).unwrap();
+            remaining = &mut remaining[length..];
+            written += length;
+        }
+        assert_eq!(written, 4);
+        assert!(matches!(
+            1_u32.encode_into_slice(remaining),
+            Err(Error::BufferTooSmall)
+        ));
+        assert!(matches!(
+            u64::MAX.encode_into_slice(&mut [0; 8]),
+            Err(Error::BufferTooSmall)
+        ));
+
+        let mut source = &buffer[..];
+        assert_eq!(u32::take_from_slice(&mut source).unwrap(), 1);
+        assert_eq!(u32::take_from_slice(&mut source).unwrap(), 300);
+        assert_eq!(u32::take_from_slice(&mut source).unwrap(), 0);
+        assert!(source.is_empty());
+
+        let truncated = &buffer[1..2];
+        let mut source = truncated;
+        assert!(matches!(
+            u32::take_from_slice(&mut source),
+            Err(Error::Truncated { needed: 2 })
+        ));
+        assert_eq!(source, truncated);
+        assert!(matches!(
+            i64::decode_from_slice(&[]),
+            Err(Error::Truncated { needed: 1 })
+        ));
+    }
+
+    #[test]
+    fn canonical() {
+        for i in u16::MIN..=u16::MAX {
+            let encoded = i.to_variable_vec().unwrap();
+            assert!(Unsigned::is_canonical(&encoded), "{i} => {encoded:02x?}");
+        }
+        for i in i16::MIN..=i16::MAX {
+            let encoded = i.to_variable_vec().unwrap();
+            assert!(Signed::is_canonical(&encoded), "{i} => {encoded:02x?}");
+        }
+        for value in [u128::from(u64::MAX), 2_u128.pow(124) - 1] {
+            let encoded = value.to_variable_vec().unwrap();
+            assert!(Unsigned::is_canonical(&encoded));
+            assert_eq!(
+                Unsigned::decode_canonical(&encoded[..]).unwrap(),
+                Unsigned::from(value)
+            );
+        }
+        for value in [
+            i128::from(i64::MIN),
+            2_i128.pow(123) - 1,
+            -(2_i128.pow(123)),
+        ] {
+            let encoded = value.to_variable_vec().unwrap();
+            assert!(Signed::is_canonical(&encoded));
+            assert_eq!(
+                Signed::decode_canonical(&encoded[..]).unwrap(),
+                Signed::from(value)
+            );
+        }
+
+        // 5, padded to two bytes, sorts after 6.
+        let padded = [0x10, 0x05];
+        assert!(!Unsigned::is_canonical(&padded));
+        assert_eq!(u8::decode_variable(&padded[..]).unwrap(), 5);
+        assert!(matches!(
+            Unsigned::decode_canonical(&padded[..]),
+            Err(Error::NonCanonical)
+        ));
+        for padded in [[0x88, 0x05], [0x77, 0xFF]] {
+            assert!(!Signed::is_canonical(&padded));
+            assert!(matches!(
+                Signed::decode_canonical(&padded[..]),
+                Err(Error::NonCanonical)
+            ));
+        }
+
+        // Extended encodings of values that fit in 16 bytes.
+        let padded = [
+            [0xFF; 16].as_slice(),
+            &[0],
+            &(2_u128.pow(124) - 2).to_be_bytes(),
+        ]
+        .concat();
+        assert_eq!(
+            u128::decode_variable(&padded[..]).unwrap(),
+            2_u128.pow(124) - 2
+        );
+        assert!(!Unsigned::is_canonical(&padded));
+        let padded = [[0xFF; 16].as_slice(), &[0], &1_i128.to_be_bytes()].concat();
+        assert!(!Signed::is_canonical(&padded));
+        // A padded extended length.
+        let padded = [
+            [0xFF; 16].as_slice(),
+            &[0x10, 0x00],
+            &u128::MAX.to_be_bytes(),
+        ]
+        .concat();
+        assert!(!Unsigned::is_canonical(&padded));
+        assert!(matches!(
+            Unsigned::decode_canonical(&padded[..]),
+            Err(Error::NonCanonical)
+        ));
+
+        // Trailing or missing bytes are not a single canonical value.
+        assert!(!Unsigned::is_canonical(&[]));
+        assert!(!Unsigned::is_canonical(&[0x01, 0x02]));
+        assert!(!Unsigned::is_canonical(&[0x11]));
+        assert!(!Signed::is_canonical(&[0x81, 0x02]));
+        assert!(!Signed::is_canonical(&[0x89]));
+    }
+
     #[test]
     fn overflow_decode() {
         let unsigned_max = u64::MAX.to_variable_vec().unwrap();
@@ -363,5 +732,1639 @@ mod tests {
         i32::decode_variable(&signed_min[..]).expect_err("i32 should overflow");
         let signed_max = i64::MAX.to_variable_vec().unwrap();
         i32::decode_variable(&signed_max[..]).expect_err("i32 should overflow");
+
+        // Values that use the same number of bytes as the largest values of the
+        // target type, but still don't fit.
+        let unsigned = (u64::from(u32::MAX) + 1).to_variable_vec().unwrap();
+        assert!(matches!(
+            u32::decode_variable(&unsigned[..]),
+            Err(Error::Overflow { target_bits: 32 })
+        ));
+        for value in [i64::from(i32::MAX) + 1, i64::from(i32::MIN) - 1] {
+            let signed = value.to_variable_vec().unwrap();
+            assert!(matches!(
+                i32::decode_variable(&signed[..]),
+                Err(Error::Overflow { target_bits: 32 })
+            ));
+        }
+        let signed = (2_i64.pow(33)).to_variable_vec().unwrap();
+        assert!(matches!(
+            i32::decode_variable(&signed[..]),
+            Err(Error::Overflow { target_bits: 32 })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "num-bigint")]
+    fn bigint() {
+        use num_bigint::{BigInt, BigUint};
+
+        // Values that fit in 128 bits are encoded identically.
+        for value in [
+            0,
+            15,
+            16,
+            u128::from(u64::MAX),
+            2_u128.pow(124) - 1,
+            u128::MAX,
+        ] {
+            let encoded = BigUint::from(value).to_variable_vec().unwrap();
+            assert_eq!(encoded, value.to_variable_vec().unwrap());
+            assert_eq!(
+                BigUint::decode_variable(&encoded[..]).unwrap(),
+                BigUint::from(value)
+            );
+        }
+        for value in [0, -1, 7, -8, i128::MIN, i128::MAX, 2_i128.pow(123) - 1] {
+            let encoded = BigInt::from(value).to_variable_vec().unwrap();
+            assert_eq!(encoded, value.to_variable_vec().unwrap());
+            assert_eq!(
+                BigInt::decode_variable(&encoded[..]).unwrap(),
+                BigInt::from(value)
+            );
+        }
+
+        let big = |power: u32| BigUint::from(2_u8).pow(power);
+        let mut unsigned = [
+            BigUint::from(0_u8),
+            BigUint::from(u128::MAX),
+            BigUint::from(u128::MAX) + 1_u8,
+            big(129),
+            big(1024) - 1_u8,
+            big(1024),
+            big(40_000),
+        ];
+        let mut signed = unsigned
+            .iter()
+            .flat_map(|value| [BigInt::from(value.clone()), -BigInt::from(value.clone())])
+            .collect::<Vec<_>>();
+        signed.extend([BigInt::from(i128::MIN) - 1, BigInt::from(i128::MIN)]);
+        unsigned.sort();
+        signed.sort();
+        let unsigned = unsigned
+            .iter()
+            .map(|value| value.to_variable_vec().unwrap())
+            .collect::<Vec<_>>();
+        let signed = signed
+            .iter()
+            .map(|value| value.to_variable_vec().unwrap())
+            .collect::<Vec<_>>();
+        for encoded in [unsigned, signed] {
+            let mut sorted = encoded.clone();
+            sorted.sort();
+            assert_eq!(encoded, sorted);
+        }
+
+        for value in [big(200), big(40_000) - 1_u8] {
+            let encoded = value.to_variable_vec().unwrap();
+            assert!(BigUnsigned::is_canonical(&encoded));
+            assert_eq!(BigUint::decode_variable(&encoded[..]).unwrap(), value);
+            assert!(matches!(
+                u128::decode_variable(&encoded[..]),
+                Err(Error::Overflow { target_bits: 128 })
+            ));
+        }
+        for value in [-BigInt::from(big(1000)), BigInt::from(big(1000))] {
+            let encoded = value.to_variable_vec().unwrap();
+            assert!(BigSigned::is_canonical(&encoded));
+            assert_eq!(BigInt::decode_variable(&encoded[..]).unwrap(), value);
+            assert!(matches!(
+                i128::decode_variable(&encoded[..]),
+                Err(Error::Overflow { target_bits: 128 })
+            ));
+        }
+
+        // Data whose sign disagrees with the prefix is rejected the same way
+        // by both signed decoders.
+        let mut mismatched = i128::MAX.to_variable_vec().unwrap();
+        let data_start = mismatched.len() - 16;
+        mismatched[data_start] |= 0x80;
+        assert!(matches!(
+            i128::decode_variable(&mismatched[..]),
+            Err(Error::Overflow { target_bits: 128 })
+        ));
+        assert!(matches!(
+            BigInt::decode_variable(&mismatched[..]),
+            Err(Error::Overflow { target_bits: 128 })
+        ));
+
+        // A padded extended encoding is not canonical.
+        let mut padded = vec![0xFF; 16];
+        padded.extend([1, 0]);
+        padded.extend(u128::MAX.to_be_bytes());
+        assert!(!BigUnsigned::is_canonical(&padded));
+        assert_eq!(
+            BigUint::decode_variable(&padded[..]).unwrap(),
+            BigUint::from(u128::MAX)
+        );
+    }
+
+    #[test]
+    fn composite() {
+        let keys = [
+            (0_u32, 0_u16, i64::MIN),
+            (0, 0, -1),
+            (0, 0, 0),
+            (0, 1, i64::MIN),
+            (0, u16::MAX, 0),
+            (1, 0, i64::MAX),
+            (15, 0, 0),
+            (16, 0, 0),
+            (u32::MAX, 0, -1),
+        ];
+        let encoded = keys
+            .iter()
+            .map(|key| key.to_variable_vec().unwrap())
+            .collect::<Vec<_>>();
+        let mut sorted = encoded.clone();
+        sorted.sort();
+        assert_eq!(encoded, sorted);
+        for (key, encoded) in keys.iter().zip(&encoded) {
+            assert_eq!(
+                <(u32, u16, i64)>::decode_from_slice(encoded).unwrap(),
+                (*key, encoded.len())
+            );
+        }
+
+        let twelve = (
+            1_u8, 2_i8, 3_u16, 4_i16, 5_u32, 6_i32, 7_u64, 8_i64, 9_u128, 10_i128, 11_usize,
+            12_isize,
+        );
+        let encoded = twelve.to_variable_vec().unwrap();
+        // Signed values above 7 need a second byte.
+        assert_eq!(encoded.len(), 15);
+        assert_eq!(Variable::decode_variable(&encoded[..]).ok(), Some(twelve));
+
+        let arrays = [[0_u64, u64::MAX], [1, 0], [1, 1], [300, 0]];
+        let encoded = arrays
+            .iter()
+            .map(|array| array.to_variable_vec().unwrap())
+            .collect::<Vec<_>>();
+        let mut sorted = encoded.clone();
+        sorted.sort();
+        assert_eq!(encoded, sorted);
+        assert_eq!(
+            <[u64; 2]>::decode_variable(&encoded[3][..]).unwrap(),
+            [300, 0]
+        );
+        assert!(matches!(
+            <[u64; 2]>::decode_variable(&encoded[3][..2]),
+            Err(Error::Truncated { needed: 3 })
+        ));
+
+        // Truncated elements report the length needed from the start of the
+        // value, not from the start of the element.
+        assert!(matches!(
+            <(u8, u64)>::decode_from_slice(&[0x01, 0x11]),
+            Err(Error::Truncated { needed: 3 })
+        ));
+        assert!(matches!(
+            <(u8, (u8, u16))>::decode_from_slice(&[0x01, 0x02, 0x11]),
+            Err(Error::Truncated { needed: 4 })
+        ));
+    }
+
+    #[test]
+    fn reverse() {
+        use core::cmp::Reverse;
+
+        assert_ordered(
+            [0, 1, 15, 16, 4095, u64::MAX - 1, u64::MAX]
+                .into_iter()
+                .map(Reverse)
+                .collect(),
+        );
+        assert_ordered(
+            [0, 1, 2_u128.pow(124) - 2, 2_u128.pow(124) - 1, u128::MAX]
+                .into_iter()
+                .map(Reverse)
+                .collect(),
+        );
+        assert_ordered(
+            [i128::MIN, -(2_i128.pow(123)), -1, 0, 1, i128::MAX]
+                .into_iter()
+                .map(Reverse)
+                .collect(),
+        );
+
+        // Reversed fields can be mixed with ascending fields.
+        assert_ordered(vec![
+            (1_u32, Reverse(10_i64), 0_u8),
+            (1, Reverse(5), 3),
+            (1, Reverse(-5), 0),
+            (2, Reverse(i64::MAX), 0),
+        ]);
+
+        let encoded = Reverse(300_u16).to_variable_vec().unwrap();
+        let inverted = encoded.iter().map(|byte| !byte).collect::<Vec<_>>();
+        assert_eq!(inverted, 300_u16.to_variable_vec().unwrap());
+        assert!(matches!(
+            Reverse::<u16>::decode_variable(&encoded[..1]),
+            Err(Error::Truncated { needed: 2 })
+        ));
+    }
+
+    #[test]
+    fn escaped() {
+        use alloc::borrow::Cow;
+
+        let mut strings = ["", "\0", "\0\0", "\0a", "a", "a\0", "ab", "b", "\u{ff}"]
+            .map(String::from)
+            .to_vec();
+        strings.sort();
+        let encoded = strings
+            .iter()
+            .map(|string| string.to_variable_vec().unwrap())
+            .collect::<Vec<_>>();
+        let mut sorted = encoded.clone();
+        sorted.sort();
+        assert_eq!(encoded, sorted);
+        for (string, encoded) in strings.iter().zip(&encoded) {
+            assert_eq!(
+                String::decode_from_slice(encoded).unwrap(),
+                (string.clone(), encoded.len())
+            );
+        }
+        assert_eq!(
+            "a\0b".to_string().to_variable_vec().unwrap(),
+            [b'a', 0, 0xFF, b'b', 0, 1]
+        );
+
+        // Strings can be mixed with integers without breaking the ordering.
+        let keys = [
+            (Cow::Borrowed("a"), 500_u64),
+            (Cow::Borrowed("a\0"), 0),
+            (Cow::Borrowed("ab"), 5),
+        ];
+        let encoded = keys
+            .iter()
+            .map(|key| key.to_variable_vec().unwrap())
+            .collect::<Vec<_>>();
+        let mut sorted = encoded.clone();
+        sorted.sort();
+        assert_eq!(encoded, sorted);
+        assert_eq!(
+            <(Cow<'_, str>, u64)>::decode_variable(&encoded[0][..]).unwrap(),
+            keys[0]
+        );
+
+        let bytes = vec![0_u8, 0xFF, 1, 0];
+        let encoded = bytes.to_variable_vec().unwrap();
+        let mut buffer = [0; 8];
+        assert_eq!(encode_bytes(&bytes, &mut buffer[..]).unwrap(), 8);
+        assert_eq!(encoded, buffer);
+        assert_eq!(Vec::<u8>::decode_variable(&encoded[..]).unwrap(), bytes);
+
+        assert!(matches!(
+            Vec::<u8>::decode_variable(&[b'a', 0, 2][..]),
+            Err(Error::InvalidEscape)
+        ));
+        assert!(matches!(
+            Vec::<u8>::decode_variable(&[b'a', 0][..]),
+            Err(Error::Truncated { needed: 3 })
+        ));
+        assert!(matches!(
+            String::decode_variable(&[0xFF, 0, 1][..]),
+            Err(Error::InvalidUtf8)
+        ));
+    }
+
+    #[test]
+    fn floats() {
+        // Bytes follow `total_cmp`, including for zeros and NaNs.
+        let mut values = [
+            -f32::NAN,
+            f32::NEG_INFINITY,
+            -1.5,
+            -0.0,
+            0.0,
+            1.5,
+            f32::INFINITY,
+            f32::NAN,
+        ];
+        values.sort_by(f32::total_cmp);
+        let encoded = values
+            .iter()
+            .map(|value| value.to_variable_vec().unwrap())
+            .collect::<Vec<_>>();
+        let mut sorted = encoded.clone();
+        sorted.sort();
+        assert_eq!(encoded, sorted);
+        for (value, encoded) in values.iter().zip(&encoded) {
+            assert_eq!(encoded.len(), 4);
+            let decoded = f32::decode_variable(&encoded[..]).unwrap();
+            assert_eq!(decoded.to_bits(), value.to_bits());
+        }
+
+        assert_eq!(
+            1.0_f64.to_variable_vec().unwrap(),
+            [0xBF, 0xF0, 0, 0, 0, 0, 0, 0]
+        );
+        assert!(matches!(
+            f64::decode_variable(&[0xBF, 0xF0][..]),
+            Err(Error::Truncated { needed: 8 })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "ordered-float")]
+    fn ordered_float() {
+        use ordered_float::{NotNan, OrderedFloat};
+
+        assert_ordered(
+            [
+                f64::NEG_INFINITY,
+                f64::MIN,
+                -1.0,
+                -f64::MIN_POSITIVE,
+                0.0,
+                f64::MIN_POSITIVE,
+                1.0,
+                f64::MAX,
+                f64::INFINITY,
+                f64::NAN,
+            ]
+            .map(OrderedFloat)
+            .to_vec(),
+        );
+        assert_ordered(
+            [-2.0, -0.5, 0.0, 0.5, 2.0, f64::INFINITY]
+                .map(|value| NotNan::new(value).unwrap())
+                .to_vec(),
+        );
+
+        // The encoded bytes compare the same way as the wrapped values,
+        // including for -0.0 and NaNs with other signs and payloads.
+        let nans = [
+            f64::NAN,
+            -f64::NAN,
+            f64::from_bits(0x7FF0_0000_0000_0001),
+            f64::from_bits(0xFFF8_0000_0000_0001),
+        ];
+        let values = [f64::NEG_INFINITY, -1.0, -0.0, 0.0, 1.0, f64::INFINITY]
+            .into_iter()
+            .chain(nans)
+            .map(OrderedFloat)
+            .collect::<Vec<_>>();
+        for a in &values {
+            for b in &values {
+                let (a_bytes, b_bytes) =
+                    (a.to_variable_vec().unwrap(), b.to_variable_vec().unwrap());
+                assert_eq!(a.cmp(b), a_bytes.cmp(&b_bytes), "{a:?} {b:?}");
+                if let (Ok(a), Ok(b)) = (NotNan::new(a.0), NotNan::new(b.0)) {
+                    let (a_bytes, b_bytes) =
+                        (a.to_variable_vec().unwrap(), b.to_variable_vec().unwrap());
+                    assert_eq!(a.cmp(&b), a_bytes.cmp(&b_bytes), "{a:?} {b:?}");
+                }
+            }
+        }
+        let f32_nans = [f32::NAN, -f32::NAN, f32::from_bits(0xFF80_0001)].map(OrderedFloat);
+        for nan in f32_nans {
+            assert_eq!(
+                nan.to_variable_vec().unwrap(),
+                OrderedFloat(f32::NAN).to_variable_vec().unwrap()
+            );
+        }
+        assert_eq!(
+            OrderedFloat(-0.0_f32).to_variable_vec().unwrap(),
+            OrderedFloat(0.0_f32).to_variable_vec().unwrap()
+        );
+
+        let encoded = OrderedFloat(f64::NAN).to_variable_vec().unwrap();
+        assert!(OrderedFloat::<f64>::decode_variable(&encoded[..])
+            .unwrap()
+            .is_nan());
+        assert!(matches!(
+            NotNan::<f64>::decode_variable(&encoded[..]),
+            Err(Error::NotANumber)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_format() {
+        use std::collections::BTreeMap;
+
+        use ::serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, PartialOrd, Eq, Ord)]
+        enum Kind {
+            Empty,
+            Named(String),
+            Pair(i8, u64),
+            Fields { id: u32, tags: Vec<char> },
+        }
+
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, PartialOrd, Eq, Ord)]
+        struct Key {
+            flag: bool,
+            tenant: Option<u16>,
+            kind: Kind,
+            path: Vec<String>,
+            counts: BTreeMap<u8, i128>,
+        }
+
+        let key = |flag, tenant, kind, path: &[&str], counts: &[(u8, i128)]| Key {
+            flag,
+            tenant,
+            kind,
+            path: path.iter().map(|part| (*part).to_string()).collect(),
+            counts: counts.iter().copied().collect(),
+        };
+        let mut keys = vec![
+            key(false, None, Kind::Empty, &[], &[]),
+            key(false, None, Kind::Empty, &[""], &[]),
+            key(false, None, Kind::Empty, &["", ""], &[]),
+            key(false, None, Kind::Empty, &["a"], &[]),
+            key(false, None, Kind::Empty, &["a\0"], &[(1, -1)]),
+            key(false, None, Kind::Empty, &["a\0"], &[(1, 0)]),
+            key(false, None, Kind::Empty, &["a\0"], &[(1, 0), (2, 0)]),
+            key(false, None, Kind::Empty, &["b"], &[]),
+            key(false, None, Kind::Named(String::new()), &[], &[]),
+            key(false, None, Kind::Named(String::from("z")), &[], &[]),
+            key(false, None, Kind::Pair(-100, 0), &[], &[]),
+            key(false, None, Kind::Pair(5, u64::MAX), &[], &[]),
+            key(
+                false,
+                None,
+                Kind::Fields {
+                    id: 1,
+                    tags: vec!['é'],
+                },
+                &[],
+                &[],
+            ),
+            key(false, Some(0), Kind::Empty, &[], &[]),
+            key(false, Some(u16::MAX), Kind::Empty, &[], &[]),
+            key(true, None, Kind::Empty, &[], &[]),
+        ];
+        keys.sort();
+        let encoded = keys
+            .iter()
+            .map(|key| serde::to_vec(key).unwrap())
+            .collect::<Vec<_>>();
+        let mut sorted = encoded.clone();
+        sorted.sort();
+        assert_eq!(encoded, sorted);
+        for (key, encoded) in keys.iter().zip(&encoded) {
+            assert_eq!(&serde::from_slice::<Key>(encoded).unwrap(), key);
+        }
+
+        // Integers use the Variable encodings.
+        assert_eq!(
+            serde::to_vec(&(300_u32, -1_i64)).unwrap(),
+            (300_u32, -1_i64).to_variable_vec().unwrap()
+        );
+        let mut written = Vec::new();
+        serde::to_writer(&mut written, &300_u32).unwrap();
+        assert_eq!(written, 300_u32.to_variable_vec().unwrap());
+
+        // Strings without escapes are borrowed from the input.
+        let encoded = serde::to_vec(&("borrowed", 1_u8)).unwrap();
+        let (string, _) = serde::from_slice::<(&str, u8)>(&encoded).unwrap();
+        assert_eq!(string, "borrowed");
+
+        assert!(matches!(
+            serde::from_slice::<u32>(&[1, 2]),
+            Err(Error::Custom(_))
+        ));
+        assert!(matches!(
+            serde::from_slice::<(u32, bool)>(&[1]),
+            Err(Error::Truncated { needed: 2 })
+        ));
+        assert!(matches!(
+            serde::from_slice::<Vec<u8>>(&[2]),
+            Err(Error::InvalidHeader)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_as() {
+        use ::serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Record {
+            #[serde(with = "serde_as::unsigned")]
+            id: u64,
+            #[serde(with = "serde_as::signed")]
+            offset: i32,
+            plain: Unsigned,
+        }
+
+        let record = Record {
+            id: 300,
+            offset: -1,
+            plain: Unsigned::from(u128::MAX),
+        };
+
+        // Human-readable formats use plain numbers.
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(
+            json,
+            format!(r#"{{"id":300,"offset":-1,"plain":{}}}"#, u128::MAX)
+        );
+        assert_eq!(serde_json::from_str::<Record>(&json).unwrap(), record);
+        assert!(serde_json::from_str::<Record>(r#"{"id":-1,"offset":0,"plain":0}"#).is_err());
+        assert!(
+            serde_json::from_str::<Record>(r#"{"id":0,"offset":4294967296,"plain":0}"#).is_err()
+        );
+
+        // Binary formats store the Variable encoding.
+        let encoded = bincode::serialize(&record).unwrap();
+        let id_bytes = 300_u64.to_variable_vec().unwrap();
+        assert_eq!(&encoded[..8], &(id_bytes.len() as u64).to_le_bytes());
+        assert_eq!(&encoded[8..8 + id_bytes.len()], &id_bytes[..]);
+        assert_eq!(bincode::deserialize::<Record>(&encoded).unwrap(), record);
+
+        // Trailing or invalid bytes are rejected.
+        let mut padded = 5_u8.to_variable_vec().unwrap();
+        padded.push(0);
+        let padded = bincode::serialize(&padded).unwrap();
+        assert!(bincode::deserialize::<Unsigned>(&padded).is_err());
+        let encoded = bincode::serialize(&Signed::from(-5_i8)).unwrap();
+        assert_eq!(
+            bincode::deserialize::<Signed>(&encoded).unwrap(),
+            Signed::from(-5_i8)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "derive")]
+    fn derive() {
+        #[derive(Variable, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+        struct Key {
+            tenant: u32,
+            #[variable(descending)]
+            revision: u64,
+            kind: Kind,
+        }
+
+        #[derive(Variable, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+        enum Kind {
+            Empty,
+            Tuple(i16, #[variable(descending)] i8),
+            #[variable(ordinal = 10)]
+            Named {
+                id: u128,
+            },
+            Next,
+        }
+
+        #[derive(Variable, Debug, PartialEq)]
+        struct Unit;
+
+        #[derive(Variable, Debug, PartialEq)]
+        struct Wrapper<T>(T, Unit);
+
+        let keys = [
+            (0, 0, Kind::Empty),
+            (0, 0, Kind::Tuple(-1, 5)),
+            (0, 0, Kind::Tuple(-1, -5)),
+            (0, 0, Kind::Tuple(0, 0)),
+            (0, 0, Kind::Named { id: 0 }),
+            (0, 0, Kind::Named { id: u128::MAX }),
+            (0, 0, Kind::Next),
+            (1, u64::MAX, Kind::Empty),
+            (1, 5, Kind::Next),
+            (1, 0, Kind::Empty),
+        ]
+        .map(|(tenant, revision, kind)| Key {
+            tenant,
+            revision,
+            kind,
+        });
+        let encoded = keys
+            .iter()
+            .map(|key| key.to_variable_vec().unwrap())
+            .collect::<Vec<_>>();
+        let mut sorted = encoded.clone();
+        sorted.sort();
+        assert_eq!(encoded, sorted);
+        for (key, encoded) in keys.iter().zip(&encoded) {
+            assert_eq!(
+                Key::decode_from_slice(encoded).unwrap(),
+                (*key, encoded.len())
+            );
+            assert_eq!(key.encoded_len(), encoded.len());
+        }
+        // The ordinal, plus the longest variant's fields.
+        assert_eq!(Kind::MAX_ENCODED_LEN, 9 + 33);
+        assert_eq!(Key::MAX_ENCODED_LEN, 5 + 9 + Kind::MAX_ENCODED_LEN);
+
+        // Explicit ordinals are encoded as-is, and following variants continue
+        // from them.
+        assert_eq!(Kind::Empty.to_variable_vec().unwrap(), [0]);
+        assert_eq!(Kind::Next.to_variable_vec().unwrap(), [11]);
+        assert!(matches!(
+            Kind::decode_variable(&[2][..]),
+            Err(Error::UnknownVariant { ordinal: 2 })
+        ));
+        let encoded = keys[5].to_variable_vec().unwrap();
+        assert!(matches!(
+            Key::decode_from_slice(&encoded[..encoded.len() - 1]),
+            Err(Error::Truncated { needed }) if needed == encoded.len()
+        ));
+
+        let wrapper = Wrapper(300_u16, Unit);
+        let encoded = wrapper.to_variable_vec().unwrap();
+        assert_eq!(encoded, 300_u16.to_variable_vec().unwrap());
+        assert_eq!(Wrapper::decode_variable(&encoded[..]).unwrap(), wrapper);
+        assert_eq!(wrapper.encoded_len(), encoded.len());
+        assert_eq!(Wrapper::<u16>::MAX_ENCODED_LEN, 3);
+        assert!(Unit.to_variable_vec().unwrap().is_empty());
+        assert_eq!(Unit::decode_variable(&[][..]).unwrap(), Unit);
+    }
+
+    #[test]
+    #[cfg(feature = "tokio")]
+    fn tokio() {
+        use ::futures::StreamExt;
+        use ::tokio::io::AsyncWriteExt;
+
+        use crate::tokio::{AsyncReadVariableExt, AsyncWriteVariableExt, VariableStream};
+
+        ::tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(async {
+                // A tiny buffer forces headers and values to be split across
+                // reads.
+                let (mut client, server) = ::tokio::io::duplex(3);
+                let mut server = ::tokio::io::BufReader::new(server);
+                let writer = ::tokio::spawn(async move {
+                    for value in [0, 300, u128::MAX, 2_u128.pow(124) - 2] {
+                        client.write_variable(&value).await.unwrap();
+                    }
+                    client
+                        .write_variable(&(1_u8, String::from("key")))
+                        .await
+                        .unwrap();
+                    client.write_all(&[0xFF, 0xFF]).await.unwrap();
+                });
+                assert_eq!(server.read_variable::<u128>().await.unwrap(), 0);
+                assert_eq!(server.read_variable::<u128>().await.unwrap(), 300);
+
+                let mut stream = VariableStream::<_, u128>::new(&mut server);
+                assert_eq!(stream.next().await.unwrap().unwrap(), u128::MAX);
+                assert_eq!(stream.next().await.unwrap().unwrap(), 2_u128.pow(124) - 2);
+                assert_eq!(
+                    server.read_variable::<(u8, String)>().await.unwrap(),
+                    (1, String::from("key"))
+                );
+                writer.await.unwrap();
+
+                // The reader ended partway through a value.
+                let mut stream = VariableStream::<_, u128>::new(server);
+                assert!(matches!(
+                    stream.next().await,
+                    Some(Err(Error::Truncated { needed: 16 }))
+                ));
+                assert!(stream.next().await.is_none());
+
+                let (client, server) = ::tokio::io::duplex(64);
+                drop(client);
+                let values = VariableStream::<_, u64>::new(::tokio::io::BufReader::new(server))
+                    .collect::<Vec<_>>()
+                    .await;
+                assert!(values.is_empty());
+            });
+    }
+
+    #[test]
+    #[cfg(feature = "futures-io")]
+    fn futures_io() {
+        use ::futures::io::Cursor;
+        use ::futures::StreamExt;
+
+        use crate::futures::{AsyncReadVariableExt, AsyncWriteVariableExt, VariableStream};
+
+        ::futures::executor::block_on(async {
+            let mut buffer = Cursor::new(Vec::new());
+            for value in [-1_i64, 0, i64::MAX, i64::MIN] {
+                buffer.write_variable(&value).await.unwrap();
+            }
+            buffer.set_position(0);
+            assert_eq!(buffer.read_variable::<i64>().await.unwrap(), -1);
+            let values = VariableStream::<_, i64>::new(buffer)
+                .map(Result::unwrap)
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(values, [0, i64::MAX, i64::MIN]);
+
+            let mut empty = Cursor::new(Vec::new());
+            assert!(matches!(
+                empty.read_variable::<i64>().await,
+                Err(Error::Truncated { needed: 1 })
+            ));
+
+            // Long values decode in a single pass, without reading past the
+            // end of the value.
+            let long = "a".repeat(4 * 1024 * 1024);
+            let mut buffer = Cursor::new(Vec::new());
+            buffer.write_variable(&long).await.unwrap();
+            buffer.write_variable(&7_u8).await.unwrap();
+            buffer.set_position(0);
+            assert_eq!(buffer.read_variable::<String>().await.unwrap(), long);
+            assert_eq!(buffer.read_variable::<u8>().await.unwrap(), 7);
+
+            // A corrupt length doesn't allocate more than the input contains.
+            #[cfg(feature = "num-bigint")]
+            {
+                let mut corrupt = vec![0xFF; 16];
+                (1_u64 << 60).encode_variable(&mut corrupt).unwrap();
+                corrupt.extend([0; 10]);
+                let mut corrupt = Cursor::new(corrupt);
+                assert!(matches!(
+                    corrupt.read_variable::<num_bigint::BigUint>().await,
+                    Err(Error::Truncated { .. })
+                ));
+            }
+        });
+    }
+
+    #[test]
+    fn encoded_len() {
+        use core::cmp::Reverse;
+
+        fn check<T: Variable + Debug>(values: impl IntoIterator<Item = T>) {
+            let mut longest = 0;
+            for value in values {
+                let encoded = value.to_variable_vec().unwrap();
+                assert_eq!(value.encoded_len(), encoded.len(), "{value:?}");
+                longest = longest.max(encoded.len());
+            }
+            assert_eq!(T::MAX_ENCODED_LEN, longest);
+        }
+
+        check(
+            (0..8)
+                .map(|bits| (1_u16 << bits) as u8 - 1)
+                .chain([u8::MAX]),
+        );
+        check(
+            (0..16)
+                .map(|bits| (1_u32 << bits) as u16 - 1)
+                .chain([u16::MAX]),
+        );
+        check(
+            (0..32)
+                .map(|bits| (1_u64 << bits) as u32 - 1)
+                .chain([u32::MAX]),
+        );
+        check(
+            (0..64)
+                .map(|bits| (1_u128 << bits) as u64 - 1)
+                .chain([u64::MAX]),
+        );
+        check((0..128).map(|bits| (1_u128 << bits) - 1).chain([u128::MAX]));
+        check((0..128).map(|bits| Unsigned((1_u128 << bits) - 1)));
+        check([0, usize::MAX]);
+        check([0, i8::MIN, i8::MAX, -8, -9, 7, 8]);
+        check([0, i16::MIN, i16::MAX]);
+        check([0, i32::MIN, i32::MAX]);
+        check([0, i64::MIN, i64::MAX]);
+        check([0, isize::MIN, isize::MAX]);
+        check(
+            (0..127)
+                .map(|bits| (1_i128 << bits) - 1)
+                .flat_map(|value| [value, -value - 1])
+                .chain([i128::MIN, i128::MAX]),
+        );
+        check([Signed::from(i128::MIN), Signed::from(-1_i8)]);
+        check([f32::MIN, f32::NAN]);
+        check([f64::MIN, f64::NAN]);
+        check([
+            (1_u8, -1_i64, Reverse(0)),
+            (u8::MAX, i64::MIN, Reverse(u16::MAX)),
+        ]);
+        check([[u32::MAX; 3], [0; 3]]);
+
+        assert_eq!(u128::MAX_ENCODED_LEN, 33);
+        assert_eq!(<(u8, String)>::MAX_ENCODED_LEN, usize::MAX);
+        assert_eq!(String::from("\0ab").encoded_len(), 6);
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_wrap)]
+    fn const_encode() {
+        const TABLE: &[u8] = ordered_varint!(u16: 300);
+        const NEGATIVE: &[u8] = ordered_varint!(i128: i128::MIN);
+
+        fn check<T: Variable, const N: usize>(
+            values: impl IntoIterator<Item = T>,
+            encode: impl Fn(&T) -> ([u8; N], usize),
+        ) {
+            for value in values {
+                let (encoded, length) = encode(&value);
+                assert_eq!(&encoded[..length], value.to_variable_vec().unwrap());
+            }
+        }
+
+        check(0..=u8::MAX, |value| const_encode_u8(*value));
+        check(0..=u16::MAX, |value| const_encode_u16(*value));
+        check(i8::MIN..=i8::MAX, |value| const_encode_i8(*value));
+        check(i16::MIN..=i16::MAX, |value| const_encode_i16(*value));
+        for bits in 0..128 {
+            let value = 1_u128 << bits;
+            for value in [value - 1, value, value + 1] {
+                check([value as u32], |value| const_encode_u32(*value));
+                check([value as u64], |value| const_encode_u64(*value));
+                check([value as usize], |value| const_encode_usize(*value));
+                check([value], |value| const_encode_u128(*value));
+                let value = value as i128;
+                for value in [value, value.wrapping_neg(), !value] {
+                    check([value as i32], |value| const_encode_i32(*value));
+                    check([value as i64], |value| const_encode_i64(*value));
+                    check([value as isize], |value| const_encode_isize(*value));
+                    check([value], |value| const_encode_i128(*value));
+                }
+            }
+        }
+        check([u128::MAX], |value| const_encode_u128(*value));
+
+        assert_eq!(TABLE, [0x11, 0x2c]);
+        assert_eq!(NEGATIVE, i128::MIN.to_variable_vec().unwrap());
+        assert_eq!(ordered_varint!(u8: u8::MAX), [0x10, 0xFF]);
+        assert_eq!(
+            ordered_varint!(i64: 7 + 1),
+            8_i64.to_variable_vec().unwrap()
+        );
+    }
+
+    #[test]
+    fn encoded_views() {
+        let mut signed = (0..127)
+            .map(|bits| (1_i128 << bits) - 1)
+            .flat_map(|value| [value, -value - 1])
+            .chain([i128::MIN, i128::MAX])
+            .collect::<Vec<_>>();
+        let encoded = signed
+            .iter()
+            .map(|value| value.to_variable_vec().unwrap())
+            .collect::<Vec<_>>();
+        let mut views = encoded
+            .iter()
+            .map(|bytes| EncodedSigned::from_prefix(bytes).unwrap())
+            .collect::<Vec<_>>();
+        signed.sort_unstable();
+        views.sort_unstable();
+        for (view, value) in views.iter().zip(&signed) {
+            assert_eq!(view.decode::<i128>().unwrap(), *value);
+            assert_eq!(view.is_negative(), *value < 0);
+            assert_eq!(view.len(), value.encoded_len());
+        }
+
+        let mut unsigned = (0..128)
+            .map(|bits| (1_u128 << bits) - 1)
+            .chain([u128::MAX])
+            .collect::<Vec<_>>();
+        let encoded = unsigned
+            .iter()
+            .map(|value| value.to_variable_vec().unwrap())
+            .collect::<Vec<_>>();
+        let mut views = encoded
+            .iter()
+            .map(|bytes| EncodedUnsigned::from_prefix(bytes).unwrap())
+            .collect::<Vec<_>>();
+        unsigned.sort_unstable();
+        views.sort_unstable();
+        for (view, value) in views.iter().zip(&unsigned) {
+            assert_eq!(view.decode::<u128>().unwrap(), *value);
+            assert!(!view.is_negative());
+            assert_eq!(view.as_bytes(), value.to_variable_vec().unwrap());
+        }
+
+        let view = EncodedUnsigned::from_prefix(&[0x11, 0x2c, 0xFF]).unwrap();
+        assert_eq!(view.as_bytes(), [0x11, 0x2c]);
+        assert_eq!(view.decode::<u16>().unwrap(), 300);
+        assert!(matches!(
+            view.decode::<u8>(),
+            Err(Error::Overflow { target_bits: 8 })
+        ));
+        let encoded = 5_u32.to_variable_vec().unwrap();
+        let view = EncodedUnsigned::from_prefix(&encoded).unwrap();
+        assert_eq!(view.decode::<i64>().unwrap(), 5);
+        let encoded = u64::MAX.to_variable_vec().unwrap();
+        let view = EncodedUnsigned::from_prefix(&encoded).unwrap();
+        assert!(matches!(
+            view.decode::<i64>(),
+            Err(Error::Overflow { target_bits: 64 })
+        ));
+        let encoded = 5_i32.to_variable_vec().unwrap();
+        let view = EncodedSigned::from_prefix(&encoded).unwrap();
+        assert_eq!(view.decode::<u64>().unwrap(), 5);
+        let encoded = (-5_i32).to_variable_vec().unwrap();
+        let view = EncodedSigned::from_prefix(&encoded).unwrap();
+        assert!(matches!(
+            view.decode::<u64>(),
+            Err(Error::Overflow { target_bits: 64 })
+        ));
+        assert!(matches!(
+            EncodedUnsigned::from_prefix(&[0x10, 0x01]),
+            Err(Error::NonCanonical)
+        ));
+        assert!(matches!(
+            EncodedSigned::from_prefix(&[0x88]),
+            Err(Error::Truncated { needed: 2 })
+        ));
+    }
+
+    #[test]
+    fn validate() {
+        let unsigned = (0..128)
+            .map(|bits| (1_u128 << bits) - 1)
+            .chain([u128::MAX])
+            .collect::<Vec<_>>();
+        let mut encoded = Vec::new();
+        let mut offsets = Vec::new();
+        for value in &unsigned {
+            offsets.push(encoded.len());
+            value.encode_variable(&mut encoded).unwrap();
+        }
+        assert_eq!(validate_unsigned(&encoded).unwrap(), unsigned.len());
+        assert_eq!(validate_unsigned(&[]).unwrap(), 0);
+        let mut reader = &encoded[..];
+        for value in &unsigned {
+            assert_eq!(
+                skip_variable::<u128, _>(&mut reader).unwrap(),
+                value.encoded_len()
+            );
+        }
+        assert!(reader.is_empty());
+        for pair in offsets.windows(2).filter(|pair| pair[1] - pair[0] > 1) {
+            let error = validate_unsigned(&encoded[..pair[1] - 1]).unwrap_err();
+            assert_eq!(error.offset, pair[0]);
+            assert!(matches!(error.error, Error::Truncated { .. }));
+        }
+
+        let signed = (0..127)
+            .map(|bits| (1_i128 << bits) - 1)
+            .flat_map(|value| [value, -value - 1])
+            .chain([i128::MIN, i128::MAX])
+            .collect::<Vec<_>>();
+        let mut encoded = Vec::new();
+        for value in &signed {
+            value.encode_variable(&mut encoded).unwrap();
+        }
+        assert_eq!(validate_signed(&encoded).unwrap(), signed.len());
+        let mut reader = &encoded[..];
+        for value in &signed {
+            assert_eq!(
+                skip_variable::<i128, _>(&mut reader).unwrap(),
+                value.encoded_len()
+            );
+        }
+        assert!(reader.is_empty());
+
+        // An extended encoding longer than the skip buffer.
+        let mut long = vec![0xFF; 16];
+        long.push(0x10);
+        long.push(100);
+        long.extend([0xAB; 116]);
+        long.push(0);
+        assert_eq!(validate_unsigned(&long).unwrap(), 2);
+        let mut reader = &long[..];
+        assert_eq!(skip_variable::<u128, _>(&mut reader).unwrap(), 134);
+        assert_eq!(reader, [0]);
+        assert!(matches!(
+            skip_variable::<u8, _>(&long[..100]),
+            Err(Error::Truncated { needed: 134 })
+        ));
+
+        let error = validate_signed(&[0x80, 0x88]).unwrap_err();
+        assert_eq!(error.offset, 1);
+        assert_eq!(
+            error.to_string(),
+            "invalid value at offset 1: input truncated: 2 bytes are needed"
+        );
+        assert!(matches!(
+            skip_variable::<String, _>(&[0xFF_u8; 100][..]),
+            Err(Error::BufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn ordered_bytes() {
+        use std::collections::{BTreeMap, HashSet};
+
+        let values = [i128::MIN, -300, -1, 0, 1, 300, i128::MAX];
+        let mut map = BTreeMap::new();
+        for value in values.into_iter().rev() {
+            let key: EncodedVariable = value.to_variable_array().unwrap();
+            assert_eq!(&key[..], &value.to_variable_vec().unwrap()[..]);
+            assert_eq!(key.decode::<i128>().unwrap(), value);
+            map.insert(key, value);
+        }
+        assert!(map.values().copied().eq(values));
+
+        let key = 300_u16.to_variable_array::<3>().unwrap();
+        assert_eq!(key.as_ref(), [0x11, 0x2c]);
+        assert_eq!(format!("{key:?}"), "OrderedBytes([17, 44])");
+        assert_eq!(key, 300_u64.to_variable_array::<3>().unwrap());
+        let unique = [key, key, 1_u16.to_variable_array().unwrap()]
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(unique.len(), 2);
+        assert!(matches!(
+            300_u16.to_variable_array::<1>(),
+            Err(Error::BufferTooSmall)
+        ));
+        assert!(u128::MAX
+            .to_variable_array::<{ u128::MAX_ENCODED_LEN }>()
+            .is_ok());
+    }
+
+    #[test]
+    fn header_info() {
+        for value in (0..128).map(|bits| 2_u128.pow(bits) - 1).chain([u128::MAX]) {
+            let encoded = value.to_variable_vec().unwrap();
+            if encoded.len() <= 16 {
+                assert_eq!(Unsigned::encoded_len_from_header(encoded[0]), encoded.len());
+            } else {
+                // Extended encodings start with the longest header.
+                assert_eq!(Unsigned::encoded_len_from_header(encoded[0]), 16);
+            }
+            assert_eq!(
+                usize::from(Unsigned::ENCODED_LEN_TABLE[usize::from(encoded[0])]),
+                Unsigned::encoded_len_from_header(encoded[0])
+            );
+        }
+
+        for value in (0..127)
+            .map(|bits| 2_i128.pow(bits) - 1)
+            .flat_map(|value| [value, -value - 1])
+            .chain([i128::MIN, i128::MAX])
+        {
+            let encoded = value.to_variable_vec().unwrap();
+            let (negative, length) = Signed::header_info(encoded[0]);
+            assert_eq!(negative, value < 0, "{value}");
+            assert_eq!(negative, encoded[0] < 0x80);
+            assert_eq!(length, encoded.len().min(16), "{value}");
+            assert_eq!(
+                usize::from(Signed::ENCODED_LEN_TABLE[usize::from(encoded[0])]),
+                length
+            );
+        }
+    }
+
+    #[test]
+    fn variable_iter() {
+        let values = [0_u128, 300, u64::MAX.into(), u128::MAX, 2_u128.pow(124) - 1];
+        let mut encoded = Vec::new();
+        let mut offsets = Vec::new();
+        for value in values {
+            offsets.push(encoded.len());
+            value.encode_variable(&mut encoded).unwrap();
+        }
+
+        let iter = VariableIter::<u128>::new(&encoded);
+        assert_eq!(iter.size_hint(), (1, Some(encoded.len())));
+        assert_eq!(iter.count_remaining().unwrap(), values.len());
+        let decoded = iter
+            .map(|(offset, value)| (offset, value.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            decoded,
+            offsets.iter().copied().zip(values).collect::<Vec<_>>()
+        );
+        let reversed = VariableIter::<u128>::new(&encoded)
+            .rev()
+            .map(|(offset, value)| (offset, value.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(reversed, decoded.iter().copied().rev().collect::<Vec<_>>());
+
+        // Iterating from both ends meets in the middle.
+        let mut iter = VariableIter::<u128>::new(&encoded);
+        assert_eq!(iter.next_back().unwrap().0, offsets[4]);
+        assert_eq!(iter.next().unwrap().0, offsets[0]);
+        assert_eq!(iter.count_remaining().unwrap(), 3);
+        assert_eq!(iter.remaining_bytes(), &encoded[offsets[1]..offsets[4]]);
+        assert_eq!(iter.nth_back(1).unwrap().0, offsets[2]);
+        assert_eq!(iter.next().unwrap().0, offsets[1]);
+        assert!(iter.next_back().is_none());
+
+        for skip in 0..values.len() {
+            let mut iter = VariableIter::<u128>::new(&encoded);
+            let (offset, value) = iter.nth(skip).unwrap();
+            assert_eq!(offset, offsets[skip]);
+            assert_eq!(value.unwrap(), values[skip]);
+            assert_eq!(iter.count_remaining().unwrap(), values.len() - skip - 1);
+            assert_eq!(iter.count(), values.len() - skip - 1);
+        }
+        assert!(VariableIter::<u128>::new(&encoded)
+            .nth(values.len())
+            .is_none());
+
+        // Composite values are skipped element by element, and signed values
+        // are skipped using their headers.
+        let pairs = [(1_u8, -1_i64), (2, i64::MIN), (3, i64::MAX)];
+        let mut encoded = Vec::new();
+        for pair in pairs {
+            pair.encode_variable(&mut encoded).unwrap();
+        }
+        let mut iter = VariableIter::<(u8, i64)>::new(&encoded);
+        assert_eq!(iter.count_remaining().unwrap(), 3);
+        assert_eq!(iter.nth(2).unwrap().1.unwrap(), (3, i64::MAX));
+        let extended = [i128::MIN, i128::MAX].to_variable_vec().unwrap();
+        assert_eq!(
+            VariableIter::<i128>::new(&extended)
+                .count_remaining()
+                .unwrap(),
+            2
+        );
+
+        // Iteration ends at the first error, which reports its offset.
+        encoded.pop();
+        let mut iter = VariableIter::<(u8, i64)>::new(&encoded);
+        assert!(matches!(
+            iter.count_remaining(),
+            Err(Error::Truncated { .. })
+        ));
+        assert!(iter.next().unwrap().1.is_ok());
+        let (offset, result) = iter.nth(1).unwrap();
+        assert!(matches!(result, Err(Error::Truncated { .. })));
+        assert_eq!(offset, encoded.len() - 9);
+        assert!(iter.next().is_none());
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+
+        // In reverse, the error is returned before the values preceding it.
+        let offsets = VariableIter::<(u8, i64)>::new(&encoded)
+            .map(|(offset, _)| offset)
+            .collect::<Vec<_>>();
+        let mut iter = VariableIter::<(u8, i64)>::new(&encoded).rev();
+        let (offset, result) = iter.next().unwrap();
+        assert!(matches!(result, Err(Error::Truncated { .. })));
+        assert_eq!(offset, encoded.len() - 9);
+        assert_eq!(
+            iter.map(|(offset, _)| offset).collect::<Vec<_>>(),
+            [offsets[1], offsets[0]]
+        );
+    }
+
+    #[test]
+    fn push_decoder() {
+        let unsigned = [0, 300, u64::MAX.into(), u128::MAX];
+        let signed = [0, -300, i64::MIN.into(), i128::MIN];
+        let mut encoded = Vec::new();
+        for (unsigned, signed) in unsigned.iter().zip(&signed) {
+            unsigned.encode_variable(&mut encoded).unwrap();
+            signed.encode_variable(&mut encoded).unwrap();
+        }
+
+        for chunk_size in 1..=encoded.len() {
+            let mut unsigned_decoder = UnsignedDecoder::new();
+            let mut signed_decoder = SignedDecoder::new();
+            let mut decoded_unsigned = Vec::new();
+            let mut decoded_signed = Vec::new();
+            for mut chunk in encoded.chunks(chunk_size) {
+                while !chunk.is_empty() {
+                    let status = if decoded_unsigned.len() > decoded_signed.len() {
+                        signed_decoder.push(chunk).unwrap().map(|value| {
+                            decoded_signed.push(i128::from(value));
+                        })
+                    } else {
+                        unsigned_decoder.push(chunk).unwrap().map(|value| {
+                            decoded_unsigned.push(u128::from(value));
+                        })
+                    };
+                    match status {
+                        DecodeStatus::NeedMore(needed) => {
+                            assert!(needed > 0);
+                            break;
+                        }
+                        DecodeStatus::Done((), consumed) => chunk = &chunk[consumed..],
+                    }
+                }
+            }
+            assert_eq!(decoded_unsigned, unsigned);
+            assert_eq!(decoded_signed, signed);
+            assert_eq!(unsigned_decoder.buffered(), 0);
+        }
+
+        // The exact remaining length is known from the first byte.
+        let mut decoder = UnsignedDecoder::new();
+        assert_eq!(decoder.push(&[]).unwrap(), DecodeStatus::NeedMore(1));
+        let encoded = u64::MAX.to_variable_vec().unwrap();
+        assert_eq!(
+            decoder.push(&encoded[..1]).unwrap(),
+            DecodeStatus::NeedMore(8)
+        );
+        assert_eq!(
+            decoder.push(&encoded[1..4]).unwrap(),
+            DecodeStatus::NeedMore(5)
+        );
+        assert_eq!(decoder.buffered(), 4);
+        assert_eq!(
+            decoder.push(&[&encoded[4..], &[1, 2]].concat()).unwrap(),
+            DecodeStatus::Done(Unsigned::from(u64::MAX), 5)
+        );
+
+        // Errors reset the decoder.
+        let mut decoder = SignedDecoder::new();
+        let mut invalid = [0xFF; 17];
+        invalid[16] = 0x01;
+        assert!(decoder.push(&invalid).is_err());
+        assert_eq!(
+            decoder.push(&[0x80]).unwrap(),
+            DecodeStatus::Done(Signed::from(0), 1)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "bytes")]
+    fn bytes() {
+        use ::bytes::{Buf, BytesMut};
+
+        use crate::bytes::{BufMutVariableExt, BufVariableExt};
+
+        let mut buffer = BytesMut::new();
+        buffer.put_u8_ordered(u8::MAX).unwrap();
+        buffer.put_u16_ordered(300).unwrap();
+        buffer.put_u32_ordered(u32::MAX).unwrap();
+        buffer.put_u64_ordered(u64::MAX).unwrap();
+        buffer.put_u128_ordered(u128::MAX).unwrap();
+        buffer.put_usize_ordered(0).unwrap();
+        buffer.put_unsigned_ordered(Unsigned::from(42_u8)).unwrap();
+        buffer.put_i8_ordered(i8::MIN).unwrap();
+        buffer.put_i16_ordered(-300).unwrap();
+        buffer.put_i32_ordered(i32::MAX).unwrap();
+        buffer.put_i64_ordered(i64::MIN).unwrap();
+        buffer.put_i128_ordered(i128::MIN).unwrap();
+        buffer.put_isize_ordered(-1).unwrap();
+        buffer.put_signed_ordered(Signed::from(-42_i8)).unwrap();
+        buffer.put_variable(&(1_u8, String::from("key"))).unwrap();
+        let encoded = buffer.freeze();
+
+        // The encoding is identical to the Write-based encoding.
+        let mut expected = Vec::new();
+        u8::MAX.encode_variable(&mut expected).unwrap();
+        300_u16.encode_variable(&mut expected).unwrap();
+        assert_eq!(&encoded[..expected.len()], &expected[..]);
+
+        // Values are decoded across chunk boundaries, regardless of where the
+        // buffer is split.
+        for split in 0..=encoded.len() {
+            let mut buffer = Buf::chain(&encoded[..split], &encoded[split..]);
+            assert_eq!(buffer.get_u8_ordered().unwrap(), u8::MAX);
+            assert_eq!(buffer.get_u16_ordered().unwrap(), 300);
+            assert_eq!(buffer.get_u32_ordered().unwrap(), u32::MAX);
+            assert_eq!(buffer.get_u64_ordered().unwrap(), u64::MAX);
+            assert_eq!(buffer.get_u128_ordered().unwrap(), u128::MAX);
+            assert_eq!(buffer.get_usize_ordered().unwrap(), 0);
+            assert_eq!(
+                buffer.get_unsigned_ordered().unwrap(),
+                Unsigned::from(42_u8)
+            );
+            assert_eq!(buffer.get_i8_ordered().unwrap(), i8::MIN);
+            assert_eq!(buffer.get_i16_ordered().unwrap(), -300);
+            assert_eq!(buffer.get_i32_ordered().unwrap(), i32::MAX);
+            assert_eq!(buffer.get_i64_ordered().unwrap(), i64::MIN);
+            assert_eq!(buffer.get_i128_ordered().unwrap(), i128::MIN);
+            assert_eq!(buffer.get_isize_ordered().unwrap(), -1);
+            assert_eq!(buffer.get_signed_ordered().unwrap(), Signed::from(-42_i8));
+            assert_eq!(
+                buffer.get_variable::<(u8, String)>().unwrap(),
+                (1, String::from("key"))
+            );
+            assert!(!buffer.has_remaining());
+            assert!(matches!(
+                buffer.get_u64_ordered(),
+                Err(Error::Truncated { needed: 1 })
+            ));
+        }
+
+        let mut small = [0_u8; 2];
+        assert!(matches!(
+            (&mut small[..]).put_u32_ordered(u32::MAX),
+            Err(Error::BufferTooSmall)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "embedded-io")]
+    fn embedded_io() {
+        use crate::embedded_io::{ReadVariableExt, WriteVariableExt};
+
+        /// A device that fails every operation.
+        struct Broken;
+
+        impl ::embedded_io::ErrorType for Broken {
+            type Error = ::embedded_io::ErrorKind;
+        }
+
+        impl ::embedded_io::Read for Broken {
+            fn read(&mut self, _buffer: &mut [u8]) -> Result<usize, Self::Error> {
+                Err(::embedded_io::ErrorKind::TimedOut)
+            }
+        }
+
+        impl ::embedded_io::Write for Broken {
+            fn write(&mut self, _bytes: &[u8]) -> Result<usize, Self::Error> {
+                Err(::embedded_io::ErrorKind::TimedOut)
+            }
+
+            fn flush(&mut self) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+
+        let values = [0, 1_700_000_000_u64, u64::MAX];
+        let mut buffer = [0; 32];
+        let mut writer = &mut buffer[..];
+        for value in values {
+            writer.write_variable(&value).unwrap();
+            writer.write_variable(&-i128::from(value)).unwrap();
+        }
+        let written = 32 - writer.len();
+
+        // The output is identical to the std encoding.
+        let mut expected = Vec::new();
+        for value in values {
+            value.encode_variable(&mut expected).unwrap();
+            (-i128::from(value)).encode_variable(&mut expected).unwrap();
+        }
+        assert_eq!(&buffer[..written], &expected[..]);
+
+        let mut reader = &buffer[..written];
+        for value in values {
+            assert_eq!(reader.read_variable::<u64>().unwrap(), value);
+            assert_eq!(reader.read_variable::<i128>().unwrap(), -i128::from(value));
+        }
+        assert!(matches!(
+            reader.read_variable::<u64>(),
+            Err(crate::embedded_io::Error::Variable(Error::Truncated {
+                needed: 1
+            }))
+        ));
+        assert!(matches!(
+            (&[0xFF_u8][..]).read_variable::<u64>(),
+            Err(crate::embedded_io::Error::Variable(Error::Overflow { .. }))
+        ));
+        assert!(matches!(
+            Broken.read_variable::<u64>(),
+            Err(crate::embedded_io::Error::Device(
+                ::embedded_io::ErrorKind::TimedOut
+            ))
+        ));
+        assert!(matches!(
+            Broken.write_variable(&1_u64),
+            Err(crate::embedded_io::Error::Device(
+                ::embedded_io::ErrorKind::TimedOut
+            ))
+        ));
+        assert!(matches!(
+            (&mut [0_u8; 2][..]).write_variable(&u64::MAX),
+            Err(crate::embedded_io::Error::Device(
+                ::embedded_io::SliceWriteError::Full
+            ))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "embedded-io-async")]
+    fn embedded_io_async() {
+        use crate::embedded_io_async::{AsyncReadVariableExt, AsyncWriteVariableExt};
+
+        ::futures::executor::block_on(async {
+            // Values longer than the stack buffer are written in chunks.
+            let key = (7_u8, "k".repeat(100));
+            let mut buffer = [0; 256];
+            let mut writer = &mut buffer[..];
+            let mut written = writer.write_variable(&u128::MAX).await.unwrap();
+            written += writer.write_variable(&-300_i16).await.unwrap();
+            written += writer.write_variable(&key).await.unwrap();
+            assert_eq!(written, 256 - writer.len());
+
+            let mut expected = u128::MAX.to_variable_vec().unwrap();
+            (-300_i16).encode_variable(&mut expected).unwrap();
+            key.encode_variable(&mut expected).unwrap();
+            assert_eq!(&buffer[..written], &expected[..]);
+
+            let mut reader = &buffer[..written];
+            assert_eq!(reader.read_variable::<u128>().await.unwrap(), u128::MAX);
+            assert_eq!(reader.read_variable::<i16>().await.unwrap(), -300);
+            // Only the bytes of each value are read.
+            assert_eq!(reader.len(), written - 35);
+            assert!(matches!(
+                reader.read_variable::<(u8, String)>().await,
+                Err(crate::embedded_io::Error::Variable(Error::BufferTooSmall))
+            ));
+
+            let mut truncated = &expected[..5];
+            assert!(matches!(
+                truncated.read_variable::<u128>().await,
+                Err(crate::embedded_io::Error::Variable(Error::Truncated {
+                    needed: 16
+                }))
+            ));
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "tokio-util")]
+    fn codec() {
+        use ::bytes::BytesMut;
+        use ::futures::{SinkExt, StreamExt};
+        use tokio_util::codec::{Decoder, Encoder, FramedRead, FramedWrite};
+
+        use crate::bytes::BufMutVariableExt;
+        use crate::codec::{FrameCodec, LengthDelimitedCodec};
+
+        // Frames are only returned once the header, length, and payload have
+        // all been received.
+        let mut codec = LengthDelimitedCodec::new();
+        let mut encoded = BytesMut::new();
+        codec.encode(&[1_u8; 300][..], &mut encoded).unwrap();
+        assert_eq!(encoded.len(), 302);
+        let mut buffer = BytesMut::new();
+        for byte in &encoded[..encoded.len() - 1] {
+            buffer.extend_from_slice(&[*byte]);
+            assert!(codec.decode(&mut buffer).unwrap().is_none());
+        }
+        buffer.extend_from_slice(&encoded[encoded.len() - 1..]);
+        assert_eq!(&codec.decode(&mut buffer).unwrap().unwrap()[..], &[1; 300]);
+        assert!(buffer.is_empty());
+
+        // The maximum frame length is enforced when encoding, and as soon as
+        // the length is decoded.
+        codec.set_max_frame_length(299);
+        assert!(matches!(
+            codec.encode(&[1_u8; 300][..], &mut BytesMut::new()),
+            Err(Error::FrameTooLarge {
+                length: 300,
+                max: 299
+            })
+        ));
+        assert!(matches!(
+            codec.decode(&mut BytesMut::from(&encoded[..2])),
+            Err(Error::FrameTooLarge {
+                length: 300,
+                max: 299
+            })
+        ));
+
+        // Complete headers are limited the same way.
+        let mut oversized = BytesMut::new();
+        oversized.put_variable(&"a".repeat(10 * 1024)).unwrap();
+        oversized.put_variable(&0_u64).unwrap();
+        assert!(matches!(
+            FrameCodec::<String>::new().decode(&mut oversized),
+            Err(Error::FrameTooLarge {
+                max: crate::codec::MAX_HEADER_LENGTH,
+                ..
+            })
+        ));
+
+        // A header whose length was corrupted is rejected instead of
+        // reserving space for it.
+        #[cfg(feature = "num-bigint")]
+        {
+            let mut corrupt = BytesMut::from(&[0xFF; 16][..]);
+            corrupt.put_variable(&(1_u64 << 60)).unwrap();
+            corrupt.extend_from_slice(&[0; 10]);
+            let capacity = corrupt.capacity();
+            assert!(matches!(
+                FrameCodec::<num_bigint::BigUint>::new().decode(&mut corrupt),
+                Err(Error::FrameTooLarge {
+                    max: crate::codec::MAX_HEADER_LENGTH,
+                    ..
+                })
+            ));
+            assert_eq!(corrupt.capacity(), capacity);
+
+            // Headers up to the limit are still accepted.
+            let header = num_bigint::BigUint::from(1_u8) << 8000_u32;
+            let mut codec = FrameCodec::new();
+            let mut encoded = BytesMut::new();
+            codec
+                .encode((header.clone(), &b"hi"[..]), &mut encoded)
+                .unwrap();
+            let (decoded, payload) = codec.decode(&mut encoded).unwrap().unwrap();
+            assert_eq!((decoded, &payload[..]), (header, &b"hi"[..]));
+            assert!(matches!(
+                codec.encode(
+                    (num_bigint::BigUint::from(1_u8) << 40_000_u32, &b""[..]),
+                    &mut encoded
+                ),
+                Err(Error::FrameTooLarge { .. })
+            ));
+        }
+
+        ::tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let (client, server) = ::tokio::io::duplex(5);
+                let mut writer = FramedWrite::new(client, FrameCodec::<(u8, u64)>::new());
+                let reader = FramedRead::new(server, FrameCodec::<(u8, u64)>::new());
+                let sender = ::tokio::spawn(async move {
+                    writer.send(((1, 0), &b""[..])).await.unwrap();
+                    writer.send(((2, u64::MAX), &b"hello"[..])).await.unwrap();
+                    writer
+                        .send(((3, 300), ::bytes::Bytes::from_static(&[0; 1000])))
+                        .await
+                        .unwrap();
+                });
+                let frames = reader
+                    .map(|frame| frame.map(|(header, payload)| (header, payload.len())))
+                    .collect::<Vec<_>>()
+                    .await;
+                sender.await.unwrap();
+                let frames = frames.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+                assert_eq!(frames, [((1, 0), 0), ((2, u64::MAX), 5), ((3, 300), 1000)]);
+            });
+    }
+
+    #[test]
+    fn io_traits() {
+        // A type implementing the `io` traits, as a no_std crate would when
+        // `std` is disabled.
+        #[derive(Default)]
+        struct Log(Vec<u8>, usize);
+
+        #[cfg(feature = "std")]
+        impl io::Write for Log {
+            fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+                self.0.extend_from_slice(bytes);
+                Ok(bytes.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        #[cfg(not(feature = "std"))]
+        impl io::Write for Log {
+            fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+                self.0.extend_from_slice(bytes);
+                Ok(())
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl io::Read for Log {
+            fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+                let mut remaining = &self.0[self.1..];
+                let read = io::Read::read(&mut remaining, buffer)?;
+                self.1 += read;
+                Ok(read)
+            }
+        }
+
+        #[cfg(not(feature = "std"))]
+        impl io::Read for Log {
+            fn read_exact(&mut self, buffer: &mut [u8]) -> io::Result<()> {
+                let remaining = &self.0[self.1..];
+                if remaining.len() < buffer.len() {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+                }
+                buffer.copy_from_slice(&remaining[..buffer.len()]);
+                self.1 += buffer.len();
+                Ok(())
+            }
+        }
+
+        let mut log = Log::default();
+        (300_u16, -1_i8).encode_variable(&mut log).unwrap();
+        assert_eq!(<(u16, i8)>::decode_variable(&mut log).unwrap(), (300, -1));
+        assert!(matches!(
+            u8::decode_variable(&mut log),
+            Err(Error::Truncated { needed: 1 })
+        ));
+
+        // Any `std::io` implementation can be used directly.
+        #[cfg(feature = "std")]
+        {
+            fn read_id<R: std::io::Read>(reader: R) -> Result<u64, Error> {
+                u64::decode_variable(reader)
+            }
+
+            let chained = std::io::Read::chain(&[0x11][..], &[0x2c, 0x01][..]);
+            let mut taken = std::io::Read::take(chained, 2);
+            assert_eq!(read_id(&mut taken).unwrap(), 300);
+            assert!(matches!(
+                read_id(&mut taken),
+                Err(Error::Truncated { needed: 1 })
+            ));
+
+            let mut output = Vec::new();
+            let writer: &mut dyn std::io::Write = &mut output;
+            300_u64.encode_variable(writer).unwrap();
+            let reader: &mut dyn std::io::Read = &mut &output[..];
+            assert_eq!(u64::decode_variable(reader).unwrap(), 300);
+        }
+    }
+
+    #[test]
+    fn io_error_conversion() {
+        // Errors can be converted to io::Error for compatibility with existing
+        // io::Result code.
+        let unsigned_max = u64::MAX.to_variable_vec().unwrap();
+        let err = io::Error::from(u32::decode_variable(&unsigned_max[..]).unwrap_err());
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = io::Error::from(u64::decode_variable(&unsigned_max[..4]).unwrap_err());
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
     }
 }
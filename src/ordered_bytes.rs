@@ -0,0 +1,113 @@
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::fmt::{self, Debug};
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+use crate::{Error, Unsigned, Variable};
+
+/// An encoded value stored inline in a buffer of `N` bytes.
+///
+/// `OrderedBytes` compares, hashes and dereferences to its encoded bytes, so
+/// it can be used as a map key that sorts in the same order as the encoded
+/// values, without allocating. Use [`Variable::to_variable_array`] to create
+/// one.
+///
+/// ```rust
+/// use std::collections::BTreeMap;
+///
+/// use ordered_varint::{EncodedVariable, Variable};
+///
+/// let mut map = BTreeMap::<EncodedVariable, &str>::new();
+/// map.insert(300_u64.to_variable_array().unwrap(), "b");
+/// map.insert(2_u64.to_variable_array().unwrap(), "a");
+/// assert_eq!(map.values().copied().collect::<Vec<_>>(), ["a", "b"]);
+///
+/// let mut key = [0; 9];
+/// let length = 300_u64.encode_into_slice(&mut key).unwrap();
+/// assert_eq!(map.get(&key[..length]), Some(&"b"));
+/// ```
+#[derive(Clone, Copy)]
+pub struct OrderedBytes<const N: usize> {
+    bytes: [u8; N],
+    length: usize,
+}
+
+/// [`OrderedBytes`] large enough to hold any integer, [`Unsigned`] or
+/// [`Signed`](crate::Signed) value.
+pub type EncodedVariable = OrderedBytes<{ Unsigned::MAX_ENCODED_LEN }>;
+
+impl<const N: usize> OrderedBytes<N> {
+    /// Encodes `value`, returning [`Error::BufferTooSmall`] if its encoding is
+    /// longer than `N` bytes.
+    pub fn new<T: Variable>(value: &T) -> Result<Self, Error> {
+        let mut bytes = [0; N];
+        let length = value.encode_into_slice(&mut bytes)?;
+        Ok(Self { bytes, length })
+    }
+
+    /// Returns the encoded bytes.
+    #[must_use]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.length]
+    }
+
+    /// Decodes the stored value.
+    pub fn decode<T: Variable>(&self) -> Result<T, Error> {
+        T::decode_variable(self.as_slice())
+    }
+}
+
+impl<const N: usize> Deref for OrderedBytes<N> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for OrderedBytes<N> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> Borrow<[u8]> for OrderedBytes<N> {
+    fn borrow(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> PartialEq for OrderedBytes<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<const N: usize> Eq for OrderedBytes<N> {}
+
+impl<const N: usize> PartialOrd for OrderedBytes<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for OrderedBytes<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<const N: usize> Hash for OrderedBytes<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl<const N: usize> Debug for OrderedBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OrderedBytes")
+            .field(&self.as_slice())
+            .finish()
+    }
+}
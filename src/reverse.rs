@@ -0,0 +1,98 @@
+use core::cmp::Reverse;
+
+use crate::io::{self, Read, Write};
+use crate::{Error, Variable};
+
+/// Encodes `T` with every byte inverted, which reverses the order of encoded
+/// values. Because the inverted encodings are still prefix-free, `Reverse<T>`
+/// can be combined with other values in composite keys.
+impl<T: Variable> Variable for Reverse<T> {
+    const MAX_ENCODED_LEN: usize = T::MAX_ENCODED_LEN;
+
+    fn encode_variable<W: Write>(&self, destination: W) -> Result<usize, Error> {
+        encode_descending(&self.0, destination)
+    }
+
+    fn encoded_len(&self) -> usize {
+        self.0.encoded_len()
+    }
+
+    fn decode_variable<R: Read>(source: R) -> Result<Self, Error> {
+        decode_descending(source).map(Reverse)
+    }
+}
+
+/// Encodes `value` with every byte inverted, as if it were wrapped in
+/// [`Reverse`]. Used by `#[derive(Variable)]` for `#[variable(descending)]`
+/// fields.
+pub fn encode_descending<T: Variable, W: Write>(value: &T, destination: W) -> Result<usize, Error> {
+    value.encode_variable(Inverted(destination))
+}
+
+/// Decodes a value written by [`encode_descending`].
+pub fn decode_descending<T: Variable, R: Read>(source: R) -> Result<T, Error> {
+    T::decode_variable(Inverted(source))
+}
+
+/// Inverts every byte read from or written to the wrapped value.
+struct Inverted<T>(T);
+
+impl<W: Write> Inverted<W> {
+    /// Writes `bytes` inverted, returning the number of bytes written. At most
+    /// one stack buffer's worth of bytes is written per call.
+    fn write_inverted(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let mut buffer = [0; 64];
+        let length = bytes.len().min(buffer.len());
+        for (inverted, byte) in buffer.iter_mut().zip(&bytes[..length]) {
+            *inverted = !byte;
+        }
+        self.0.write_all(&buffer[..length])?;
+        Ok(length)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for Inverted<W> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.write_inverted(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<W: Write> Write for Inverted<W> {
+    fn write_all(&mut self, mut bytes: &[u8]) -> io::Result<()> {
+        while !bytes.is_empty() {
+            let written = self.write_inverted(bytes)?;
+            bytes = &bytes[written..];
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Read for Inverted<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let read = self.0.read(buffer)?;
+        invert(&mut buffer[..read]);
+        Ok(read)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<R: Read> Read for Inverted<R> {
+    fn read_exact(&mut self, buffer: &mut [u8]) -> io::Result<()> {
+        self.0.read_exact(buffer)?;
+        invert(buffer);
+        Ok(())
+    }
+}
+
+fn invert(bytes: &mut [u8]) {
+    for byte in bytes {
+        *byte = !*byte;
+    }
+}
@@ -0,0 +1,681 @@
+//! A [serde](https://serde.rs) data format that produces byte-orderable
+//! encodings.
+//!
+//! Values serialized with [`to_vec`] sort in the same order as the `Ord`
+//! implementation that `#[derive(PartialOrd, Ord)]` produces for the same type:
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+//! struct Key {
+//!     tenant: u32,
+//!     name: String,
+//!     revision: i64,
+//! }
+//!
+//! let first = Key { tenant: 1, name: String::from("a"), revision: 5 };
+//! let second = Key { tenant: 1, name: String::from("ab"), revision: -1 };
+//! let first_bytes = ordered_varint::serde::to_vec(&first).unwrap();
+//! let second_bytes = ordered_varint::serde::to_vec(&second).unwrap();
+//! assert!(first_bytes < second_bytes);
+//! assert_eq!(
+//!     ordered_varint::serde::from_slice::<Key>(&first_bytes).unwrap(),
+//!     first
+//! );
+//! ```
+//!
+//! The format is not self-describing:
+//!
+//! - Integers are encoded using [`Unsigned`](crate::Unsigned) and
+//!   [`Signed`](crate::Signed), and `char`s are encoded as a `u32`.
+//! - `bool`s are encoded as a single `0` or `1` byte.
+//! - Floats use the total-order encoding of [`Variable`].
+//! - Strings and byte strings use the escaped, terminated encoding of
+//!   [`encode_bytes`].
+//! - `None` is encoded as `0`, and `Some` is encoded as `1` followed by the
+//!   value.
+//! - Sequences and maps write `1` before each element or entry, and are
+//!   terminated by `0`, which causes shorter sequences to sort before longer
+//!   sequences that begin with the same elements.
+//! - Tuples and structs are encoded as their fields, in order. Field names are
+//!   not included.
+//! - Enum variants are encoded as their index, followed by their contents.
+//!   Variants sort in declaration order.
+//! - Units, unit structs and newtype wrappers don't add any bytes.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use ::serde::de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+use ::serde::{ser, Deserialize, Serialize};
+
+use crate::io::Write;
+use crate::{encode_bytes, Error, Variable};
+
+/// Serializes `value` into a new `Vec<u8>`.
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut output = Vec::new();
+    to_writer(&mut output, value)?;
+    Ok(output)
+}
+
+/// Serializes `value` into `writer`.
+pub fn to_writer<W: Write, T: Serialize + ?Sized>(writer: W, value: &T) -> Result<(), Error> {
+    value.serialize(&mut Serializer::new(writer))
+}
+
+/// Deserializes a `T` from `bytes`, returning an error if any bytes remain
+/// after the value.
+pub fn from_slice<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> Result<T, Error> {
+    let mut deserializer = Deserializer::new(bytes);
+    let value = T::deserialize(&mut deserializer)?;
+    if deserializer.input.is_empty() {
+        Ok(value)
+    } else {
+        Err(de::Error::custom("trailing bytes after value"))
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+/// The marker written before each element of a sequence or entry of a map.
+const ELEMENT: u8 = 1;
+/// The marker written after the last element of a sequence or entry of a map.
+const END: u8 = 0;
+
+/// A serde serializer that writes byte-orderable encodings.
+#[derive(Debug)]
+pub struct Serializer<W> {
+    output: W,
+}
+
+impl<W: Write> Serializer<W> {
+    /// Returns a serializer that writes to `output`.
+    pub const fn new(output: W) -> Self {
+        Self { output }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+
+    fn write_byte(&mut self, byte: u8) -> Result<(), Error> {
+        self.output.write_all(&[byte])?;
+        Ok(())
+    }
+
+    fn write_variable<T: Variable>(&mut self, value: &T) -> Result<(), Error> {
+        value.encode_variable(&mut self.output)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::Serializer for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.write_byte(u8::from(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.write_variable(&v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.write_variable(&v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.write_variable(&v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        self.write_variable(&v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), Error> {
+        self.write_variable(&v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.write_variable(&v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.write_variable(&v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.write_variable(&v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.write_variable(&v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Error> {
+        self.write_variable(&v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.write_variable(&v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.write_variable(&v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.write_variable(&u32::from(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        encode_bytes(v, &mut self.output)?;
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.write_byte(0)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        self.write_byte(1)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Error> {
+        self.write_variable(&variant_index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.write_variable(&variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        self.write_variable(&variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        self.write_variable(&variant_index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<W: Write> ser::SerializeSeq for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.write_byte(ELEMENT)?;
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.write_byte(END)
+    }
+}
+
+impl<W: Write> ser::SerializeMap for &mut Serializer<W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.write_byte(ELEMENT)?;
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.write_byte(END)
+    }
+}
+
+macro_rules! impl_serialize_fields {
+    ($trait:ident, $method:ident $(, $key:ident)?) => {
+        impl<W: Write> ser::$trait for &mut Serializer<W> {
+            type Ok = ();
+            type Error = Error;
+
+            fn $method<T: Serialize + ?Sized>(
+                &mut self,
+                $($key: &'static str,)?
+                value: &T,
+            ) -> Result<(), Error> {
+                value.serialize(&mut **self)
+            }
+
+            fn end(self) -> Result<(), Error> {
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_serialize_fields!(SerializeTuple, serialize_element);
+impl_serialize_fields!(SerializeTupleStruct, serialize_field);
+impl_serialize_fields!(SerializeTupleVariant, serialize_field);
+impl_serialize_fields!(SerializeStruct, serialize_field, _key);
+impl_serialize_fields!(SerializeStructVariant, serialize_field, _key);
+
+/// A serde deserializer that reads values written by [`Serializer`].
+#[derive(Debug)]
+pub struct Deserializer<'de> {
+    input: &'de [u8],
+    original_length: usize,
+}
+
+impl<'de> Deserializer<'de> {
+    /// Returns a deserializer that reads from `input`.
+    #[must_use]
+    pub const fn new(input: &'de [u8]) -> Self {
+        Self {
+            input,
+            original_length: input.len(),
+        }
+    }
+
+    /// Returns the bytes that have not been deserialized yet.
+    #[must_use]
+    pub const fn remaining(&self) -> &'de [u8] {
+        self.input
+    }
+
+    fn take_byte(&mut self) -> Result<u8, Error> {
+        let (&byte, remaining) = self.input.split_first().ok_or(Error::Truncated {
+            needed: self.original_length - self.input.len() + 1,
+        })?;
+        self.input = remaining;
+        Ok(byte)
+    }
+
+    fn take_variable<T: Variable>(&mut self) -> Result<T, Error> {
+        T::take_from_slice(&mut self.input).map_err(|err| match err {
+            Error::Truncated { needed } => Error::Truncated {
+                needed: self.original_length - self.input.len() + needed,
+            },
+            other => other,
+        })
+    }
+
+    /// Returns the next escaped byte string, borrowing it from the input if it
+    /// contains no escaped bytes.
+    fn take_bytes(&mut self) -> Result<Bytes<'de>, Error> {
+        let escape = self.input.iter().position(|&byte| byte == 0);
+        if let Some(escape) = escape {
+            if self.input.get(escape + 1) == Some(&1) {
+                let (bytes, remaining) = self.input.split_at(escape);
+                self.input = &remaining[2..];
+                return Ok(Bytes::Borrowed(bytes));
+            }
+        }
+        self.take_variable().map(Bytes::Owned)
+    }
+
+    fn take_marker(&mut self) -> Result<bool, Error> {
+        match self.take_byte()? {
+            END => Ok(false),
+            ELEMENT => Ok(true),
+            _ => Err(Error::InvalidHeader),
+        }
+    }
+}
+
+/// Decoded bytes that are either borrowed from the input or owned.
+enum Bytes<'de> {
+    Borrowed(&'de [u8]),
+    Owned(Vec<u8>),
+}
+
+macro_rules! deserialize_variable {
+    ($($method:ident => $visit:ident),+ $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                visitor.$visit(self.take_variable()?)
+            }
+        )+
+    };
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    deserialize_variable!(
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    );
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(de::Error::custom(
+            "deserialize_any is not supported because the format is not self-describing",
+        ))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.take_byte()? {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            other => Err(de::Error::invalid_value(
+                de::Unexpected::Unsigned(u64::from(other)),
+                &"0 or 1",
+            )),
+        }
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let value = self.take_variable::<u32>()?;
+        let ch = char::from_u32(value).ok_or_else(|| {
+            <Error as de::Error>::invalid_value(
+                de::Unexpected::Unsigned(u64::from(value)),
+                &"a char",
+            )
+        })?;
+        visitor.visit_char(ch)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.take_bytes()? {
+            Bytes::Borrowed(bytes) => visitor
+                .visit_borrowed_str(core::str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?),
+            Bytes::Owned(bytes) => {
+                visitor.visit_string(String::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)?)
+            }
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.take_bytes()? {
+            Bytes::Borrowed(bytes) => visitor.visit_borrowed_bytes(bytes),
+            Bytes::Owned(bytes) => visitor.visit_byte_buf(bytes),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.take_byte()? {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            _ => Err(Error::InvalidHeader),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(Terminated(self))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(Counted {
+            deserializer: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(Terminated(self))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u32(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Accesses the elements of a sequence or the entries of a map that are
+/// prefixed by [`ELEMENT`] and terminated by [`END`].
+struct Terminated<'a, 'de>(&'a mut Deserializer<'de>);
+
+impl<'de> SeqAccess<'de> for Terminated<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.0.take_marker()? {
+            seed.deserialize(&mut *self.0).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for Terminated<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        self.next_element_seed(seed)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.0)
+    }
+}
+
+/// Accesses a fixed number of fields.
+struct Counted<'a, 'de> {
+    deserializer: &'a mut Deserializer<'de>,
+    remaining: usize,
+}
+
+impl<'de> SeqAccess<'de> for Counted<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> EnumAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let index = self.take_variable::<u32>()?;
+        let value = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
@@ -0,0 +1,161 @@
+//! Helpers for serializing primitive integers as [`Unsigned`] or [`Signed`]
+//! values using `#[serde(with = "...")]`.
+//!
+//! [`Unsigned`] and [`Signed`] implement `Serialize` and `Deserialize`. When
+//! the format is human-readable, values are serialized as plain numbers.
+//! Otherwise, values are serialized as bytes containing their
+//! [`Variable`] encoding.
+//!
+//! The [`unsigned`] and [`signed`] modules allow a field to use this
+//! representation without changing its type:
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Record {
+//!     #[serde(with = "ordered_varint::serde_as::unsigned")]
+//!     id: u64,
+//!     #[serde(with = "ordered_varint::serde_as::signed")]
+//!     offset: i32,
+//! }
+//! ```
+
+use core::fmt::{self, Display};
+
+use ::serde::de::{self, SeqAccess, Unexpected, Visitor};
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Signed, Unsigned, Variable};
+
+macro_rules! impl_serde {
+    ($ty:ident, $inner:ty, $deserialize_inner:ident, $visitor:ident, $expecting:literal) => {
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if serializer.is_human_readable() {
+                    <$inner>::from(*self).serialize(serializer)
+                } else {
+                    let mut buffer = [0; <$ty as Variable>::MAX_ENCODED_LEN];
+                    let length = self
+                        .encode_into_slice(&mut buffer)
+                        .map_err(::serde::ser::Error::custom)?;
+                    serializer.serialize_bytes(&buffer[..length])
+                }
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                if deserializer.is_human_readable() {
+                    deserializer.$deserialize_inner($visitor)
+                } else {
+                    deserializer.deserialize_bytes($visitor)
+                }
+            }
+        }
+
+        struct $visitor;
+
+        impl $visitor {
+            fn decode_encoded<E: de::Error>(self, encoded: &[u8]) -> Result<$ty, E> {
+                match $ty::decode_from_slice(encoded) {
+                    Ok((value, length)) if length == encoded.len() => Ok(value),
+                    Ok(_) => Err(E::invalid_length(encoded.len(), &self)),
+                    Err(err) => Err(E::custom(err)),
+                }
+            }
+        }
+
+        impl<'de> Visitor<'de> for $visitor {
+            type Value = $ty;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str($expecting)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<$ty, E> {
+                self.visit_u128(u128::from(v))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<$ty, E> {
+                self.visit_i128(i128::from(v))
+            }
+
+            fn visit_u128<E: de::Error>(self, v: u128) -> Result<$ty, E> {
+                <$inner>::try_from(v)
+                    .map($ty::from)
+                    .map_err(|_| E::invalid_value(Unexpected::Other("out of range integer"), &self))
+            }
+
+            fn visit_i128<E: de::Error>(self, v: i128) -> Result<$ty, E> {
+                <$inner>::try_from(v)
+                    .map($ty::from)
+                    .map_err(|_| E::invalid_value(Unexpected::Other("out of range integer"), &self))
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<$ty, E> {
+                self.decode_encoded(v)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<$ty, A::Error> {
+                let mut buffer = [0; <$ty as Variable>::MAX_ENCODED_LEN];
+                let mut length = 0;
+                while let Some(byte) = seq.next_element()? {
+                    let slot = buffer
+                        .get_mut(length)
+                        .ok_or_else(|| de::Error::invalid_length(length + 1, &self))?;
+                    *slot = byte;
+                    length += 1;
+                }
+                self.decode_encoded(&buffer[..length])
+            }
+        }
+    };
+}
+
+impl_serde!(
+    Unsigned,
+    u128,
+    deserialize_u128,
+    UnsignedVisitor,
+    "an unsigned integer or its ordered-varint encoding"
+);
+impl_serde!(
+    Signed,
+    i128,
+    deserialize_i128,
+    SignedVisitor,
+    "a signed integer or its ordered-varint encoding"
+);
+
+macro_rules! serde_with_module {
+    ($name:ident, $ty:ident) => {
+        #[doc = concat!("Serializes a primitive integer as a [`", stringify!($ty), "`] using")]
+        #[doc = concat!("`#[serde(with = \"ordered_varint::serde_as::", stringify!($name), "\")]`.")]
+        pub mod $name {
+            use super::*;
+
+            #[doc = concat!("Serializes `value` as a [`", stringify!($ty), "`].")]
+            pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                T: Copy + Into<$ty>,
+                S: Serializer,
+            {
+                Into::<$ty>::into(*value).serialize(serializer)
+            }
+
+            #[doc = concat!("Deserializes a [`", stringify!($ty), "`] and converts it to `T`.")]
+            pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+            where
+                T: TryFrom<$ty>,
+                T::Error: Display,
+                D: Deserializer<'de>,
+            {
+                T::try_from($ty::deserialize(deserializer)?).map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+serde_with_module!(unsigned, Unsigned);
+serde_with_module!(signed, Signed);
@@ -1,31 +1,117 @@
-use std::io::{Read, Write};
-use std::num::TryFromIntError;
+use core::num::TryFromIntError;
 
-use crate::Variable;
+use crate::error::read_exact;
+use crate::io::{Read, Write};
+use crate::{Error, Unsigned, Variable};
 
 /// A signed integer value.
 ///
-/// This type encodes values in the range `-2.pow(123)..2.pow(123)` by using the
-/// first 5 bits to denote a signed byte `length`. This length ranges from
+/// This type encodes values in the range `-2.pow(123)+1..2.pow(123)-1` by using
+/// the first 5 bits to denote a signed byte `length`. This length ranges from
 /// `-15..=15`. The number of bytes read is always absolute, but the sign of the
 /// length is used to determine the overall sign of the encoded value. The
 /// remaining 3 bits of the first byte and any additional bytes are then
 /// used to store the integer in big-endian encoding.
+///
+/// Every possible first byte is used by this scheme, so values outside of this
+/// range use an extended encoding. Positive values are prefixed with the 16
+/// byte encoding of `2.pow(123) - 1` (sixteen `0xFF` bytes), and negative
+/// values are prefixed with the 16 byte encoding of `-2.pow(123)` (sixteen
+/// `0x00` bytes). The prefix is followed by the number of bytes beyond 16
+/// needed to store the value, encoded as an [`Unsigned`] whose bytes are
+/// inverted for negative values. Finally, the value's big-endian two's
+/// complement bytes are written. Values in this range require 33 bytes, but
+/// all other values are encoded identically to previous versions of this
+/// crate.
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Ord, PartialOrd)]
 pub struct Signed(i128);
 
+/// The largest value that is encoded without the extended encoding.
+const SHORT_MAX: i128 = 2_i128.pow(123) - 2;
+/// The smallest value that is encoded without the extended encoding.
+const SHORT_MIN: i128 = -(2_i128.pow(123)) + 1;
+
 impl Signed {
+    /// Returns true if `encoded` contains exactly one value that is encoded
+    /// using its shortest possible encoding.
+    ///
+    /// Encoded values only sort in numeric order when every value is encoded
+    /// canonically. [`Variable::encode_variable`] always produces canonical
+    /// encodings, but [`Variable::decode_variable`] accepts encodings that are
+    /// padded with additional sign bits.
+    #[must_use]
+    pub fn is_canonical(mut encoded: &[u8]) -> bool {
+        Self::decode_canonical_bytes::<_, 16>(&mut encoded).is_ok() && encoded.is_empty()
+    }
+
+    /// Decodes a variable length value from `source`, returning
+    /// [`Error::NonCanonical`] if the value was not encoded using its shortest
+    /// possible encoding.
+    pub fn decode_canonical<R: Read>(source: R) -> Result<Self, Error> {
+        Self::decode_canonical_bytes(source).map(|buffer| Self(i128::from_be_bytes(buffer)))
+    }
+
+    /// The total encoded length of a value, indexed by the value's first byte.
+    ///
+    /// See [`Self::header_info`] for details. A value is negative if its first
+    /// byte is less than `0x80`.
+    pub const ENCODED_LEN_TABLE: [u8; 256] = {
+        let mut table = [0; 256];
+        let mut header = 0;
+        while header < 256 {
+            table[header] = Self::header_info(header as u8).1 as u8;
+            header += 1;
+        }
+        table
+    };
+
+    /// Returns whether a value whose encoding starts with `header` is
+    /// negative, and the value's total length including the header.
+    ///
+    /// The top 5 bits of the header store a signed length whose magnitude is
+    /// the number of bytes that follow it, so the length is always in
+    /// `1..=16`. Values too large for a 16 byte encoding use an extended
+    /// encoding, which begins with sixteen `0xFF` bytes for positive values or
+    /// sixteen `0x00` bytes for negative values. For those values, the total
+    /// length can only be determined by reading the bytes following the
+    /// prefix.
+    #[must_use]
+    pub const fn header_info(header: u8) -> (bool, usize) {
+        let (negative, length) = Self::decode_header(header);
+        (negative, length + 1)
+    }
+
+    /// Returns whether the value is negative and the number of bytes that
+    /// follow the first byte.
+    const fn decode_header(first_byte: u8) -> (bool, usize) {
+        let encoded_length = first_byte as usize >> 3;
+        if encoded_length >= 2_usize.pow(4) {
+            (false, encoded_length - 2_usize.pow(4))
+        } else {
+            (true, 2_usize.pow(4) - (encoded_length + 1))
+        }
+    }
+
     pub(crate) fn encode_be_bytes<W: Write, const N: usize>(
         mut value: [u8; N],
         mut output: W,
-    ) -> std::io::Result<usize> {
+    ) -> Result<usize, Error> {
         let check_bits = if N == 16 {
             // We reserve 5 bits for a signed 4 bit number, ranging from -16..=15.
+            // Values that don't fit use the extended encoding.
             let reserved = value[0] >> 3;
-            match reserved {
-                0 => 0,
-                0b11111 => 0xFF,
-                _ => return Err(std::io::Error::from(std::io::ErrorKind::InvalidData)),
+            let extended = match reserved {
+                0 => value[..] == (SHORT_MAX + 1).to_be_bytes(),
+                0b11111 => value[..] == (SHORT_MIN - 1).to_be_bytes(),
+                _ => true,
+            };
+            if extended {
+                return Unsigned::encode_extended(&value, reserved >> 4 == 1, output);
+            }
+            if reserved == 0 {
+                0
+            } else {
+                0xFF
             }
         } else if value[0] >> 7 == 0 {
             // positive
@@ -76,33 +162,165 @@ impl Signed {
         Ok(total_length)
     }
 
+    /// Encodes `value` into the start of the returned buffer in a `const`
+    /// context, returning the buffer and the encoded length. The bytes are
+    /// identical to those written by [`Self::encode_be_bytes`].
+    pub(crate) const fn encode_const(value: i128) -> ([u8; Unsigned::max_encoded_len(16)], usize) {
+        if value > SHORT_MAX || value < SHORT_MIN {
+            return Unsigned::encode_extended_const(value.to_be_bytes(), value < 0);
+        }
+
+        // The top 5 bits of the first byte store a signed length whose
+        // magnitude is the number of bytes that follow it. The remaining bits
+        // are already sign extended.
+        let significant_bits = if value < 0 {
+            u128::BITS - value.leading_ones()
+        } else {
+            u128::BITS - value.leading_zeros()
+        } as usize;
+        let length = (significant_bits + 5).div_ceil(8);
+        let length_header = if value < 0 { 16 - length } else { 15 + length };
+        let mut encoded = Unsigned::const_tail(value.to_be_bytes(), length);
+        encoded[0] = (encoded[0] & 0b111) | ((length_header as u8) << 3);
+        (encoded, length)
+    }
+
+    /// Returns the total length of the value encoded at the start of
+    /// `encoded`, reading only its header bytes.
+    pub(crate) fn encoded_len_in_slice(encoded: &[u8]) -> Result<usize, Error> {
+        let Some(&first_byte) = encoded.first() else {
+            return Err(Error::Truncated { needed: 1 });
+        };
+        let (_, length) = Self::header_info(first_byte);
+        match encoded.get(..Unsigned::EXTENDED_PREFIX_LEN) {
+            Some(prefix) if prefix == [0xFF; Unsigned::EXTENDED_PREFIX_LEN] => {
+                Unsigned::extended_len_in_slice(encoded, false)
+            }
+            Some(prefix) if prefix == [0; Unsigned::EXTENDED_PREFIX_LEN] => {
+                Unsigned::extended_len_in_slice(encoded, true)
+            }
+            _ if encoded.len() < length => Err(Error::Truncated { needed: length }),
+            _ => Ok(length),
+        }
+    }
+
     pub(crate) fn decode_variable_bytes<R: Read, const N: usize>(
+        input: R,
+    ) -> Result<[u8; N], Error> {
+        Self::decode_bytes(input, false)
+    }
+
+    pub(crate) fn decode_canonical_bytes<R: Read, const N: usize>(
+        input: R,
+    ) -> Result<[u8; N], Error> {
+        Self::decode_bytes(input, true)
+    }
+
+    fn decode_bytes<R: Read, const N: usize>(
         mut input: R,
-    ) -> std::io::Result<[u8; N]> {
-        let mut buffer = [0_u8; N];
-        input.read_exact(&mut buffer[0..1])?;
-        let first_byte = buffer[0];
-        let encoded_length = first_byte as usize >> 3;
-        let (negative, length) = if encoded_length >= 2_usize.pow(4) {
-            (false, encoded_length - 2_usize.pow(4))
+        canonical: bool,
+    ) -> Result<[u8; N], Error> {
+        let mut first_byte = [0];
+        read_exact(&mut input, &mut first_byte, 1)?;
+        let buffer = Self::decode_remaining_bytes(first_byte[0], &mut input, canonical)?;
+        let Some(negative) = Self::extended_prefix_sign(first_byte[0], &buffer) else {
+            return Ok(buffer);
+        };
+
+        let overflow = Error::Overflow {
+            target_bits: N as u32 * 8,
+        };
+        let (extra_bytes, length_bytes) =
+            Unsigned::decode_extended_length(&mut input, negative, canonical)?;
+        // i128 values never need more than 16 bytes.
+        if extra_bytes > 0 {
+            return Err(overflow);
+        }
+
+        let mut buffer = [0; N];
+        read_exact(
+            &mut input,
+            &mut buffer,
+            Unsigned::EXTENDED_PREFIX_LEN + length_bytes + N,
+        )?;
+        if (buffer[0] >> 7 == 1) != negative {
+            return Err(overflow);
+        }
+        if canonical && !Self::is_canonical_extended(&buffer) {
+            return Err(Error::NonCanonical);
+        }
+        Ok(buffer)
+    }
+
+    /// Returns whether the value is negative if `first_byte` and the value
+    /// `decoded` from the bytes following it are the prefix of an extended
+    /// encoding.
+    pub(crate) fn extended_prefix_sign<const N: usize>(
+        first_byte: u8,
+        decoded: &[u8; N],
+    ) -> Option<bool> {
+        let (negative, length) = Self::decode_header(first_byte);
+        if N != 16 || length != 15 {
+            None
+        } else if negative && decoded[..] == (SHORT_MIN - 1).to_be_bytes() {
+            Some(true)
+        } else if !negative && decoded[..] == (SHORT_MAX + 1).to_be_bytes() {
+            Some(false)
         } else {
-            (true, 2_usize.pow(4) - (encoded_length + 1))
+            None
+        }
+    }
+
+    /// Returns true if the big-endian two's complement bytes of an extended
+    /// encoding are the shortest encoding of the value.
+    pub(crate) fn is_canonical_extended(data: &[u8]) -> bool {
+        // The first 9 bits must not all be sign extension.
+        let redundant_sign = match data {
+            [0, second, ..] => second >> 7 == 0,
+            [0xFF, second, ..] => second >> 7 == 1,
+            _ => false,
+        };
+        !redundant_sign
+            && (data.len() > Unsigned::EXTENDED_PREFIX_LEN
+                || data.try_into().is_ok_and(|data| {
+                    !(SHORT_MIN..=SHORT_MAX).contains(&i128::from_be_bytes(data))
+                }))
+    }
+
+    /// Decodes the bytes that follow `first_byte` in an encoding that does not
+    /// use the extended length.
+    pub(crate) fn decode_remaining_bytes<R: Read, const N: usize>(
+        first_byte: u8,
+        mut input: R,
+        canonical: bool,
+    ) -> Result<[u8; N], Error> {
+        let mut buffer = [0_u8; N];
+        buffer[0] = first_byte;
+        let (negative, length) = Self::decode_header(first_byte);
+        let overflow = Error::Overflow {
+            target_bits: N as u32 * 8,
         };
         if length > N {
-            return Err(std::io::Error::from(std::io::ErrorKind::InvalidData));
+            return Err(overflow);
         }
 
-        input.read_exact(&mut buffer[N - length..])?;
+        read_exact(&mut input, &mut buffer[N - length..], length + 1)?;
+        // A canonical encoding must need all of its bytes: the 8 data bits
+        // following the header can't all be sign extension.
+        let sign_bits = if negative { 0xFF } else { 0 };
+        if canonical && length > 0 && (first_byte << 5) | (buffer[N - length] >> 3) == sign_bits {
+            return Err(Error::NonCanonical);
+        }
 
         match N - length {
             0 => {
-                // We overwrote our first byte, but the first byte has some 3
-                // bits of data we need to preserve.
-                let mut first_bits = first_byte & 0b111;
-                if negative {
-                    first_bits ^= 0b111;
+                // We overwrote our first byte, so the first byte's 3 bits of
+                // data must only be sign extension, and the top bit of the
+                // bytes read must agree with the sign.
+                let sign_bits = if negative { 0b111 } else { 0 };
+                if first_byte & 0b111 != sign_bits || (buffer[0] >> 7 == 1) != negative {
+                    return Err(overflow);
                 }
-                buffer[0] |= first_bits << 5;
             }
             1 => {
                 // Clear the top 3 bits of the top byte, and negate if needed.
@@ -135,44 +353,32 @@ impl Signed {
 }
 
 impl Variable for Signed {
-    fn encode_variable<W: Write>(&self, output: W) -> std::io::Result<usize> {
+    const MAX_ENCODED_LEN: usize = Unsigned::max_encoded_len(16);
+
+    fn encode_variable<W: Write>(&self, output: W) -> Result<usize, Error> {
         Self::encode_be_bytes(self.0.to_be_bytes(), output)
     }
 
-    fn decode_variable<R: Read>(mut input: R) -> std::io::Result<Self> {
-        let mut buffer = [0_u8; 16];
-        input.read_exact(&mut buffer[0..1])?;
-
-        let encoded_length = buffer[0] as usize >> 3;
-        let (negative, length) = if encoded_length >= 2_usize.pow(4) {
-            (false, encoded_length - 2_usize.pow(4))
-        } else {
-            (true, 2_usize.pow(4) - (encoded_length + 1))
-        };
-
-        input.read_exact(&mut buffer[16 - length..])?;
-
-        if length < 15 {
-            buffer[15 - length] |= buffer[0] & 0b111;
-            if negative {
-                buffer[15 - length] ^= 0b1111_1000;
-            }
-            buffer[0] = 0;
-        } else {
-            buffer[0] &= 0b111;
-            if negative {
-                buffer[0] ^= 0b1111_1000;
-            }
+    fn encoded_len(&self) -> usize {
+        if !(SHORT_MIN..=SHORT_MAX).contains(&self.0) {
+            return Self::MAX_ENCODED_LEN;
         }
+        // The header's 5 bits are followed by the value's significant bits,
+        // excluding sign extension.
+        let magnitude = if self.0 < 0 { !self.0 } else { self.0 };
+        let bits = (i128::BITS - magnitude.leading_zeros()) as usize + 5;
+        bits.div_ceil(8)
+    }
 
-        if negative {
-            for byte in &mut buffer[0..15 - length] {
-                *byte ^= 0xFF;
-            }
-        }
+    fn decode_variable<R: Read>(input: R) -> Result<Self, Error> {
+        let buffer = Self::decode_variable_bytes(input)?;
 
         Ok(Self(i128::from_be_bytes(buffer)))
     }
+
+    fn encoded_len_in_slice(source: &[u8]) -> Result<usize, Error> {
+        Self::encoded_len_in_slice(source)
+    }
 }
 
 macro_rules! impl_primitive_from_varint {
@@ -208,6 +414,12 @@ impl_primitive_from_varint!(i16);
 impl_primitive_from_varint!(i32);
 impl_primitive_from_varint!(i64);
 impl_primitive_from_varint!(isize);
+impl_primitive_from_varint!(u8);
+impl_primitive_from_varint!(u16);
+impl_primitive_from_varint!(u32);
+impl_primitive_from_varint!(u64);
+impl_primitive_from_varint!(u128);
+impl_primitive_from_varint!(usize);
 
 impl From<Signed> for i128 {
     fn from(value: Signed) -> Self {
@@ -0,0 +1,23 @@
+//! Asynchronous encoding and decoding using [`tokio`]'s I/O traits.
+//!
+//! ```rust
+//! use ordered_varint::tokio::{AsyncReadVariableExt, AsyncWriteVariableExt};
+//!
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! let (mut client, server) = tokio::io::duplex(64);
+//! let mut server = tokio::io::BufReader::new(server);
+//! client.write_variable(&300_u64).await.unwrap();
+//! assert_eq!(server.read_variable::<u64>().await.unwrap(), 300);
+//! # });
+//! ```
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+
+use ::tokio::io::{AsyncBufRead, AsyncWrite};
+use futures_core::Stream;
+
+use crate::asynchronous::impl_async_variable;
+
+impl_async_variable!();
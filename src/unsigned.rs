@@ -1,26 +1,89 @@
-use std::io::{Read, Write};
-use std::num::TryFromIntError;
+use core::num::TryFromIntError;
 
-use crate::Variable;
+use crate::error::read_exact;
+use crate::io::{Read, Write};
+use crate::{Error, Variable};
 
 /// An unsigned integer value
 ///
-/// This type encodes values in the range `0..2.pow(124)` by using the first 4
-/// bits to denote an unsigned byte `length`. This length ranges from `0..=15`.
-/// The remaining 4 bits of the first byte and any additional bytes are then
-/// used to store the integer in big-endian encoding.
+/// This type encodes values in the range `0..2.pow(124) - 1` by using the
+/// first 4 bits to denote an unsigned byte `length`. This length ranges from
+/// `0..=15`. The remaining 4 bits of the first byte and any additional bytes
+/// are then used to store the integer in big-endian encoding.
+///
+/// Every possible first byte is used by this scheme, so larger values are
+/// encoded using the 16 byte encoding of `2.pow(124) - 1` (sixteen `0xFF`
+/// bytes) as a prefix. The prefix is followed by the number of big-endian
+/// bytes beyond 16 needed to store the value, encoded as an `Unsigned`, and
+/// then the value's big-endian bytes. Values in this range require 33 bytes,
+/// but all values below `2.pow(124) - 1` are encoded identically to previous
+/// versions of this crate.
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Ord, PartialOrd)]
 pub struct Unsigned(pub(crate) u128);
 
+/// The value whose 16 byte encoding prefixes extended encodings.
+const EXTENDED_MIN: u128 = 2_u128.pow(124) - 1;
+
 impl Unsigned {
+    /// The number of bytes in the prefix of an extended encoding, which is
+    /// also the minimum number of big-endian bytes an extended encoding
+    /// contains.
+    pub(crate) const EXTENDED_PREFIX_LEN: usize = 16;
+
+    /// The total encoded length of a value, indexed by the value's first byte.
+    ///
+    /// See [`Self::encoded_len_from_header`] for details.
+    pub const ENCODED_LEN_TABLE: [u8; 256] = {
+        let mut table = [0; 256];
+        let mut header = 0;
+        while header < 256 {
+            table[header] = Self::encoded_len_from_header(header as u8) as u8;
+            header += 1;
+        }
+        table
+    };
+
+    /// Returns the total length, including the header, of a value whose
+    /// encoding starts with `header`.
+    ///
+    /// The top 4 bits of the header store the number of bytes that follow it,
+    /// so the result is always in `1..=16`. Values too large for a 16 byte
+    /// encoding use an extended encoding, which begins with sixteen `0xFF`
+    /// bytes. For those values, the total length can only be determined by
+    /// reading the bytes following the prefix.
+    #[must_use]
+    pub const fn encoded_len_from_header(header: u8) -> usize {
+        (header >> 4) as usize + 1
+    }
+
+    /// Returns true if `encoded` contains exactly one value that is encoded
+    /// using its shortest possible encoding.
+    ///
+    /// Encoded values only sort in numeric order when every value is encoded
+    /// canonically. [`Variable::encode_variable`] always produces canonical
+    /// encodings, but [`Variable::decode_variable`] accepts encodings that are
+    /// padded with leading zeros.
+    #[must_use]
+    pub fn is_canonical(mut encoded: &[u8]) -> bool {
+        Self::decode_canonical_bytes::<_, 16>(&mut encoded).is_ok() && encoded.is_empty()
+    }
+
+    /// Decodes a variable length value from `source`, returning
+    /// [`Error::NonCanonical`] if the value was not encoded using its shortest
+    /// possible encoding.
+    pub fn decode_canonical<R: Read>(source: R) -> Result<Self, Error> {
+        Self::decode_canonical_bytes(source).map(|buffer| Self(u128::from_be_bytes(buffer)))
+    }
+
     pub(crate) fn encode_be_bytes<W: Write, const N: usize>(
         mut value: [u8; N],
         mut output: W,
-    ) -> std::io::Result<usize> {
+    ) -> Result<usize, Error> {
         // Because we encode "extra bytes" in 4 bits, we must keep the extra
-        // bytes to 15 or less. This only affects 128-bit encoding
-        if N == 16 && value[0] >> 4 != 0 {
-            return Err(std::io::Error::from(std::io::ErrorKind::InvalidData));
+        // bytes to 15 or less. This only affects 128-bit encoding, which uses
+        // the extended encoding for the remaining values.
+        if N == 16 && (value[0] >> 4 != 0 || value[..] == EXTENDED_MIN.to_be_bytes()) {
+            return Self::encode_extended(&value, false, output);
         }
 
         let (total_length, extra_bytes) = value
@@ -54,22 +117,217 @@ impl Unsigned {
         Ok(total_length)
     }
 
+    /// Writes the extended encoding of `data`, which must contain at least
+    /// [`Self::EXTENDED_PREFIX_LEN`] bytes. When `inverted` is true, the prefix and
+    /// length are inverted so that longer values sort first.
+    pub(crate) fn encode_extended<W: Write>(
+        data: &[u8],
+        inverted: bool,
+        mut output: W,
+    ) -> Result<usize, Error> {
+        let extra_bytes = (data.len() - Self::EXTENDED_PREFIX_LEN) as u64;
+        let mut length = [0; 9];
+        let length_bytes = Self::encode_be_bytes(extra_bytes.to_be_bytes(), &mut length[..])?;
+        let length = &mut length[..length_bytes];
+        let mut prefix = [0xFF; Self::EXTENDED_PREFIX_LEN];
+        if inverted {
+            prefix = [0; Self::EXTENDED_PREFIX_LEN];
+            for byte in &mut *length {
+                *byte ^= 0xFF;
+            }
+        }
+
+        output.write_all(&prefix)?;
+        output.write_all(length)?;
+        output.write_all(data)?;
+
+        Ok(Self::EXTENDED_PREFIX_LEN + length_bytes + data.len())
+    }
+
+    /// Encodes `value` into the start of the returned buffer in a `const`
+    /// context, returning the buffer and the encoded length. The bytes are
+    /// identical to those written by [`Self::encode_be_bytes`].
+    ///
+    /// Values encode to the same bytes regardless of the width of their type,
+    /// so every unsigned primitive can be widened to a `u128`.
+    pub(crate) const fn encode_const(value: u128) -> ([u8; Self::max_encoded_len(16)], usize) {
+        if value >> 124 != 0 || value == EXTENDED_MIN {
+            return Self::encode_extended_const(value.to_be_bytes(), false);
+        }
+
+        // The top 4 bits of the first byte store the number of bytes that
+        // follow it.
+        let significant_bits = (u128::BITS - value.leading_zeros()) as usize;
+        let length = (significant_bits + 4).div_ceil(8);
+        let mut encoded = Self::const_tail(value.to_be_bytes(), length);
+        encoded[0] |= ((length - 1) as u8) << 4;
+        (encoded, length)
+    }
+
+    /// Encodes 16 big-endian bytes using the extended encoding in a `const`
+    /// context, identically to [`Self::encode_extended`].
+    pub(crate) const fn encode_extended_const(
+        data: [u8; 16],
+        inverted: bool,
+    ) -> ([u8; Self::max_encoded_len(16)], usize) {
+        // No bytes follow the prefix beyond the 16 data bytes, so the length
+        // is a single zero byte.
+        let (prefix, length) = if inverted { (0, 0xFF) } else { (0xFF, 0) };
+        let mut encoded = [prefix; Self::max_encoded_len(16)];
+        encoded[Self::EXTENDED_PREFIX_LEN] = length;
+        let mut index = 0;
+        while index < data.len() {
+            encoded[Self::EXTENDED_PREFIX_LEN + 1 + index] = data[index];
+            index += 1;
+        }
+        (encoded, encoded.len())
+    }
+
+    /// Returns a buffer that starts with the last `length` bytes of `bytes`.
+    pub(crate) const fn const_tail(
+        bytes: [u8; 16],
+        length: usize,
+    ) -> [u8; Self::max_encoded_len(16)] {
+        let mut encoded = [0; Self::max_encoded_len(16)];
+        let mut index = 0;
+        while index < length {
+            encoded[index] = bytes[bytes.len() - length + index];
+            index += 1;
+        }
+        encoded
+    }
+
+    /// Returns the longest encoding of a primitive integer that is `width`
+    /// bytes wide. 16 byte integers need the extended encoding for their
+    /// largest values.
+    pub(crate) const fn max_encoded_len(width: usize) -> usize {
+        if width < 16 {
+            width + 1
+        } else {
+            Self::EXTENDED_PREFIX_LEN + 1 + 16
+        }
+    }
+
+    /// Returns the total length of the value encoded at the start of
+    /// `encoded`, reading only its header bytes.
+    pub(crate) fn encoded_len_in_slice(encoded: &[u8]) -> Result<usize, Error> {
+        let Some(&first_byte) = encoded.first() else {
+            return Err(Error::Truncated { needed: 1 });
+        };
+        let length = Self::encoded_len_from_header(first_byte);
+        if encoded.get(..Self::EXTENDED_PREFIX_LEN) == Some(&[0xFF; Self::EXTENDED_PREFIX_LEN]) {
+            return Self::extended_len_in_slice(encoded, false);
+        }
+        if encoded.len() < length {
+            return Err(Error::Truncated { needed: length });
+        }
+        Ok(length)
+    }
+
+    /// Returns the total length of the extended encoding at the start of
+    /// `encoded`, whose prefix has already been checked. `inverted` is true
+    /// when the length is inverted.
+    pub(crate) fn extended_len_in_slice(encoded: &[u8], inverted: bool) -> Result<usize, Error> {
+        let (extra_bytes, length_bytes) =
+            Self::decode_extended_length(&encoded[Self::EXTENDED_PREFIX_LEN..], inverted, false)?;
+        let length = usize::try_from(extra_bytes)
+            .ok()
+            .and_then(|extra_bytes| {
+                extra_bytes.checked_add(Self::EXTENDED_PREFIX_LEN * 2 + length_bytes)
+            })
+            .ok_or(Error::Overflow {
+                target_bits: usize::BITS,
+            })?;
+        if encoded.len() < length {
+            return Err(Error::Truncated { needed: length });
+        }
+        Ok(length)
+    }
+
     pub(crate) fn decode_variable_bytes<R: Read, const N: usize>(
+        input: R,
+    ) -> Result<[u8; N], Error> {
+        Self::decode_bytes(input, false)
+    }
+
+    pub(crate) fn decode_canonical_bytes<R: Read, const N: usize>(
+        input: R,
+    ) -> Result<[u8; N], Error> {
+        Self::decode_bytes(input, true)
+    }
+
+    fn decode_bytes<R: Read, const N: usize>(
         mut input: R,
-    ) -> std::io::Result<[u8; N]> {
+        canonical: bool,
+    ) -> Result<[u8; N], Error> {
+        let mut first_byte = [0];
+        read_exact(&mut input, &mut first_byte, 1)?;
+        let buffer = Self::decode_remaining_bytes(first_byte[0], &mut input, canonical)?;
+        if Self::is_extended_prefix(first_byte[0], &buffer) {
+            let (extra_bytes, length_bytes) =
+                Self::decode_extended_length(&mut input, false, canonical)?;
+            // u128 values never need more than 16 bytes.
+            if extra_bytes > 0 {
+                return Err(Error::Overflow {
+                    target_bits: N as u32 * 8,
+                });
+            }
+
+            let mut buffer = [0; N];
+            read_exact(
+                &mut input,
+                &mut buffer,
+                Self::EXTENDED_PREFIX_LEN + length_bytes + N,
+            )?;
+            if canonical && !Self::is_canonical_extended(&buffer) {
+                return Err(Error::NonCanonical);
+            }
+            Ok(buffer)
+        } else {
+            Ok(buffer)
+        }
+    }
+
+    /// Returns true if `first_byte` and the value `decoded` from the bytes
+    /// following it are the prefix of an extended encoding.
+    pub(crate) fn is_extended_prefix<const N: usize>(first_byte: u8, decoded: &[u8; N]) -> bool {
+        N == 16 && first_byte == 0xFF && decoded[..] == EXTENDED_MIN.to_be_bytes()
+    }
+
+    /// Returns true if the big-endian bytes of an extended encoding are the
+    /// shortest encoding of the value.
+    pub(crate) fn is_canonical_extended(data: &[u8]) -> bool {
+        data.first() != Some(&0)
+            && (data.len() > Self::EXTENDED_PREFIX_LEN || data >= &EXTENDED_MIN.to_be_bytes()[..])
+    }
+
+    /// Decodes the bytes that follow `first_byte` in an encoding that does not
+    /// use the extended length.
+    pub(crate) fn decode_remaining_bytes<R: Read, const N: usize>(
+        first_byte: u8,
+        mut input: R,
+        canonical: bool,
+    ) -> Result<[u8; N], Error> {
         let mut buffer = [0_u8; N];
-        input.read_exact(&mut buffer[0..1])?;
-        let first_byte = buffer[0];
+        buffer[0] = first_byte;
         let length = (first_byte >> 4) as usize;
-        if length > N {
-            return Err(std::io::Error::from(std::io::ErrorKind::InvalidData));
+        // When the length is N, the first byte's data bits would need to be
+        // stored above the top byte of the buffer.
+        if length > N || (length == N && first_byte & 0b1111 != 0) {
+            return Err(Error::Overflow {
+                target_bits: N as u32 * 8,
+            });
+        }
+        read_exact(&mut input, &mut buffer[N - length..], length + 1)?;
+        // A canonical encoding must need all of its bytes: the 8 data bits
+        // following the header can't all be zero.
+        if canonical && length > 0 && (first_byte << 4) | (buffer[N - length] >> 4) == 0 {
+            return Err(Error::NonCanonical);
         }
-        input.read_exact(&mut buffer[N - length..])?;
         match N - length {
             0 => {
-                // We overwrite the first byte with the read operation, so we need
-                // to fill back in the bits from the first byte.
-                buffer[0] |= first_byte & 0b1111;
+                // The first byte held no data, and the read operation has
+                // already overwritten it.
             }
             1 => {
                 // Clear the top 4 bits of the first byte. The lower 4 bits may
@@ -85,18 +343,61 @@ impl Unsigned {
         }
         Ok(buffer)
     }
+
+    /// Reads the number of bytes beyond [`Self::EXTENDED_PREFIX_LEN`] stored
+    /// in an extended encoding, after the prefix has already been read.
+    /// Returns the number of extra bytes and the number of bytes read.
+    pub(crate) fn decode_extended_length<R: Read>(
+        mut input: R,
+        inverted: bool,
+        canonical: bool,
+    ) -> Result<(u64, usize), Error> {
+        let mut encoded = [0; 16];
+        read_exact(&mut input, &mut encoded[..1], Self::EXTENDED_PREFIX_LEN + 1)?;
+        if inverted {
+            encoded[0] ^= 0xFF;
+        }
+        let length = usize::from(encoded[0] >> 4);
+        read_exact(
+            &mut input,
+            &mut encoded[1..=length],
+            Self::EXTENDED_PREFIX_LEN + 1 + length,
+        )?;
+        if inverted {
+            for byte in &mut encoded[1..=length] {
+                *byte ^= 0xFF;
+            }
+        }
+        let value = Self::decode_remaining_bytes(encoded[0], &encoded[1..=length], canonical)?;
+        Ok((u64::from_be_bytes(value), length + 1))
+    }
 }
 
 impl Variable for Unsigned {
-    fn encode_variable<W: Write>(&self, output: W) -> std::io::Result<usize> {
+    const MAX_ENCODED_LEN: usize = Self::max_encoded_len(16);
+
+    fn encode_variable<W: Write>(&self, output: W) -> Result<usize, Error> {
         Self::encode_be_bytes(self.0.to_be_bytes(), output)
     }
 
-    fn decode_variable<R: Read>(input: R) -> std::io::Result<Self> {
+    fn encoded_len(&self) -> usize {
+        if self.0 >= EXTENDED_MIN {
+            return Self::MAX_ENCODED_LEN;
+        }
+        // The header's 4 bits are followed by the value's significant bits.
+        let bits = (u128::BITS - self.0.leading_zeros()) as usize + 4;
+        bits.div_ceil(8)
+    }
+
+    fn decode_variable<R: Read>(input: R) -> Result<Self, Error> {
         let buffer = Self::decode_variable_bytes(input)?;
 
         Ok(Self(u128::from_be_bytes(buffer)))
     }
+
+    fn encoded_len_in_slice(source: &[u8]) -> Result<usize, Error> {
+        Self::encoded_len_in_slice(source)
+    }
 }
 
 macro_rules! impl_primitive_from_varint {
@@ -132,6 +433,12 @@ impl_primitive_from_varint!(u16);
 impl_primitive_from_varint!(u32);
 impl_primitive_from_varint!(u64);
 impl_primitive_from_varint!(usize);
+impl_primitive_from_varint!(i8);
+impl_primitive_from_varint!(i16);
+impl_primitive_from_varint!(i32);
+impl_primitive_from_varint!(i64);
+impl_primitive_from_varint!(i128);
+impl_primitive_from_varint!(isize);
 
 impl From<Unsigned> for u128 {
     fn from(value: Unsigned) -> Self {
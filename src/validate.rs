@@ -0,0 +1,130 @@
+use core::fmt::{self, Display};
+
+use crate::error::read_exact;
+use crate::io::Read;
+use crate::{Error, Signed, Unsigned, Variable};
+
+/// The number of bytes [`skip_variable`] buffers while determining a value's
+/// length.
+const SKIP_BUFFER_LEN: usize = 64;
+
+/// An error from validating values encoded back to back.
+#[derive(Debug)]
+pub struct ValidationError {
+    /// The offset of the first malformed value.
+    pub offset: usize,
+    /// The reason the value is malformed.
+    pub error: Error,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid value at offset {}: {}", self.offset, self.error)
+    }
+}
+
+impl core::error::Error for ValidationError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Checks that `encoded` contains only complete [`Unsigned`] values, returning
+/// the number of values.
+///
+/// Only the header bytes of each value are read, so values aren't checked to
+/// be canonical or to fit in any particular type.
+///
+/// ```rust
+/// use ordered_varint::{validate_unsigned, Variable};
+///
+/// let mut encoded = [0; 6];
+/// let mut unused = &mut encoded[..];
+/// for value in [1_u64, 300, 70_000] {
+///     value.encode_variable(&mut unused).unwrap();
+/// }
+/// assert_eq!(validate_unsigned(&encoded).unwrap(), 3);
+///
+/// let error = validate_unsigned(&encoded[..5]).unwrap_err();
+/// assert_eq!(error.offset, 3);
+/// ```
+pub fn validate_unsigned(encoded: &[u8]) -> Result<usize, ValidationError> {
+    validate(encoded, Unsigned::encoded_len_in_slice)
+}
+
+/// Checks that `encoded` contains only complete [`Signed`] values, returning
+/// the number of values.
+///
+/// Only the header bytes of each value are read, so values aren't checked to
+/// be canonical or to fit in any particular type.
+pub fn validate_signed(encoded: &[u8]) -> Result<usize, ValidationError> {
+    validate(encoded, Signed::encoded_len_in_slice)
+}
+
+fn validate(
+    encoded: &[u8],
+    encoded_len_in_slice: fn(&[u8]) -> Result<usize, Error>,
+) -> Result<usize, ValidationError> {
+    let mut offset = 0;
+    let mut count = 0;
+    while offset < encoded.len() {
+        offset += encoded_len_in_slice(&encoded[offset..])
+            .map_err(|error| ValidationError { offset, error })?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Reads past a single `T` value in `reader` without decoding it, returning
+/// the number of bytes skipped.
+///
+/// The value's length is determined using [`Variable::encoded_len_in_slice`],
+/// buffering up to 64 bytes of the value on the stack. For integers,
+/// [`Unsigned`] and [`Signed`], the length is read from the header, so values
+/// aren't checked to fit in `T`, and the bytes of extended encodings longer
+/// than the buffer are discarded as they are read. Other types whose values
+/// can be longer than 64 bytes return [`Error::BufferTooSmall`] for values
+/// longer than that.
+///
+/// ```rust
+/// use ordered_varint::{skip_variable, Variable};
+///
+/// let mut encoded = [0; 4];
+/// let mut unused = &mut encoded[..];
+/// 70_000_u64.encode_variable(&mut unused).unwrap();
+/// (-1_i8).encode_variable(&mut unused).unwrap();
+///
+/// let mut reader = &encoded[..];
+/// assert_eq!(skip_variable::<u64, _>(&mut reader).unwrap(), 3);
+/// assert_eq!(i8::decode_variable(reader).unwrap(), -1);
+/// ```
+pub fn skip_variable<T: Variable, R: Read>(mut reader: R) -> Result<usize, Error> {
+    let mut buffer = [0; SKIP_BUFFER_LEN];
+    let mut filled = 0;
+    loop {
+        let needed = match T::encoded_len_in_slice(&buffer[..filled]) {
+            Ok(length) => return Ok(length),
+            Err(Error::Truncated { needed }) => needed,
+            Err(err) => return Err(err),
+        };
+
+        if filled < buffer.len() {
+            let end = needed.min(buffer.len());
+            read_exact(&mut reader, &mut buffer[filled..end], needed)?;
+            filled = end;
+        } else if T::MAX_ENCODED_LEN <= buffer.len() {
+            // Only extended encodings with extra bytes, which don't fit in
+            // `T`, reach this. Their headers fit in the buffer, so `needed` is
+            // the total length read from the header.
+            let mut skipped = filled;
+            while skipped < needed {
+                let chunk = (needed - skipped).min(buffer.len());
+                read_exact(&mut reader, &mut buffer[..chunk], needed)?;
+                skipped += chunk;
+            }
+            return Ok(needed);
+        } else {
+            return Err(Error::BufferTooSmall);
+        }
+    }
+}